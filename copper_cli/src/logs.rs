@@ -0,0 +1,103 @@
+use copper_lib::{
+	instance::log_parser::{LogLevel, LogLine, LogParser, LogRecord},
+	Result,
+};
+use std::{
+	fs::File,
+	io::{stdout, BufWriter, IsTerminal, Write},
+	path::Path,
+};
+
+/// Prints the game's output, parsing, filtering and colorizing log events if the game writes them as XML.
+pub struct LogPrinter {
+	parser: Option<LogParser>,
+	min_level: Option<LogLevel>,
+	logger: Option<String>,
+	json: Option<BufWriter<File>>,
+	color: bool,
+}
+
+impl LogPrinter {
+	pub fn new(
+		xml: bool,
+		min_level: Option<LogLevel>,
+		logger: Option<String>,
+		json: Option<&Path>,
+	) -> Result<Self> {
+		Ok(Self {
+			parser: xml.then(LogParser::new),
+			min_level,
+			logger,
+			json: match json {
+				Some(path) => Some(BufWriter::new(File::create(path)?)),
+				None => None,
+			},
+			color: stdout().is_terminal(),
+		})
+	}
+
+	pub fn stdout(&mut self, line: &str) -> Result<()> {
+		let lines = match &mut self.parser {
+			Some(parser) => parser.push_line(line),
+			None => vec![LogLine::Text(line.to_string())],
+		};
+		for line in lines {
+			self.print(line)?;
+		}
+		Ok(())
+	}
+
+	pub fn stderr(&mut self, line: &str) {
+		if self.color {
+			eprintln!("\x1b[31m{line}\x1b[0m");
+		} else {
+			eprintln!("{line}");
+		}
+	}
+
+	/// Print anything the parser is still holding on to, and flush the JSON log.
+	pub fn finish(&mut self) -> Result<()> {
+		if let Some(line) = self.parser.as_mut().and_then(LogParser::finish) {
+			self.print(line)?;
+		}
+		if let Some(json) = &mut self.json {
+			json.flush()?;
+		}
+		Ok(())
+	}
+
+	fn print(&mut self, line: LogLine) -> Result<()> {
+		let record = match line {
+			LogLine::Record(record) => record,
+			LogLine::Text(text) => {
+				println!("{text}");
+				return Ok(());
+			}
+		};
+		if !self.matches(&record) {
+			return Ok(());
+		}
+
+		if let Some(json) = &mut self.json {
+			serde_json::to_writer(&mut *json, &record)?;
+			writeln!(json)?;
+		}
+		let rendered = record.render();
+		match (self.color, record.level) {
+			(false, _) | (true, LogLevel::Info) => println!("{rendered}"),
+			(true, LogLevel::Trace | LogLevel::Debug) => println!("\x1b[2m{rendered}\x1b[0m"),
+			(true, LogLevel::Warn) => println!("\x1b[33m{rendered}\x1b[0m"),
+			(true, LogLevel::Error | LogLevel::Fatal) => println!("\x1b[31m{rendered}\x1b[0m"),
+		}
+		Ok(())
+	}
+
+	fn matches(&self, record: &LogRecord) -> bool {
+		let level = self.min_level.is_none_or(|min| record.level >= min);
+		let logger = self
+			.logger
+			.as_ref()
+			.is_none_or(|logger| record.logger.starts_with(logger.as_str()));
+		level && logger
+	}
+}
@@ -1,12 +1,39 @@
+mod logs;
+
 use clap::{Parser, Subcommand};
 use copper_lib::{
-	api::mojang::{fetch_version_manifest, get_profile},
-	instance::Instance,
-	store::save_url_cache,
+	api::{
+		modrinth::Modrinth,
+		mojang::{fetch_version_manifest, get_profile},
+	},
+	instance::{
+		command::LaunchCommand,
+		config::InstanceConfig,
+		jvm::{parse_memory, GcPreset},
+		loader::Loader,
+		lock::{ContentKind, Lockfile, LOCK_FILE},
+		log_parser::LogLevel,
+		logging::LogConfig,
+		mods::{self, ModError, ModQuery, ModrinthProvider, Scanned},
+		process::GameEvent,
+		quick_play::QuickPlay,
+		Instance,
+	},
+	mappings::Mappings,
+	resolver::{resolve_with, Dependency, DependencyKind, ResolveError},
+	store::{save_url_cache, set_offline},
 	Paths,
 };
-use std::error::Error;
-use tracing::info;
+use logs::LogPrinter;
+use std::{
+	collections::BTreeMap,
+	error::Error,
+	fs,
+	path::{Path, PathBuf},
+	process::ExitCode,
+};
+use tokio::signal::ctrl_c;
+use tracing::{info, warn};
 use tracing_subscriber::FmtSubscriber;
 
 #[derive(Parser)]
@@ -18,35 +45,816 @@ struct Args {
 
 #[derive(Subcommand)]
 enum Commands {
-	/// Launch specific version of Minecraft
-	Launch { version: String },
+	/// Launch an instance, or a specific version of Minecraft
+	Launch {
+		/// Name of an instance. If there's no instance with this name, it's used as a version of Minecraft instead
+		instance: String,
+		/// Join a server on startup (`host` or `host:port`)
+		#[clap(long, conflicts_with_all = ["world", "realm"])]
+		join: Option<String>,
+		/// Load a singleplayer world on startup, by its folder name
+		#[clap(long, conflicts_with = "realm")]
+		world: Option<String>,
+		/// Join a Realm on startup, by its ID
+		#[clap(long)]
+		realm: Option<String>,
+		/// Override the instance's log4j config: profile, xml, plain, none, or a path to a config file (relative to the instance's directory)
+		#[clap(long)]
+		logging: Option<LogConfig>,
+		/// Only show log events at or above this level
+		#[clap(long)]
+		log_level: Option<LogLevel>,
+		/// Only show log events from loggers starting with this name
+		#[clap(long)]
+		logger: Option<String>,
+		/// Save log events to a file as JSON lines
+		#[clap(long)]
+		log_json: Option<PathBuf>,
+		/// Link and check everything again, instead of trusting what was set up before
+		#[clap(long)]
+		repair: bool,
+		/// Print the command the game would be started with (with secrets redacted) instead of starting it. Nothing is downloaded, so the instance has to have been launched before
+		#[clap(long)]
+		dry_run: bool,
+	},
+	/// Write a shell script that launches an instance without copper
+	ExportScript {
+		instance: String,
+		/// Where to write the script. Defaults to `launch.sh` in the instance's directory
+		#[clap(long, short)]
+		output: Option<PathBuf>,
+	},
+	/// Deobfuscate a crash report or stack trace with Mojang's mappings
+	Deobf {
+		/// Path to the crash report
+		report: PathBuf,
+		/// Version of Minecraft the report is from. Read from the report if not given
+		#[clap(long, conflicts_with = "instance")]
+		version: Option<String>,
+		/// Instance the report is from
+		#[clap(long)]
+		instance: Option<String>,
+		/// Use the server's mappings instead of the client's
+		#[clap(long)]
+		server: bool,
+		/// Write the deobfuscated report to a file instead of stdout
+		#[clap(long, short)]
+		output: Option<PathBuf>,
+	},
+	/// Manage instances
+	Instance {
+		#[clap(subcommand)]
+		command: InstanceCommands,
+	},
+	/// Manage an instance's mods, resource packs and shaders, from Modrinth
+	Mod {
+		#[clap(subcommand)]
+		command: ModCommands,
+	},
+	/// Link exactly the files in an instance's lockfile (`copper.lock`) into place, fetching any that aren't in the store
+	Install { instance: String },
+	/// Update an instance's mods, resource packs and shaders to their newest compatible versions, and save them to its lockfile
+	Update { instance: String },
+}
+
+#[derive(Subcommand)]
+enum ModCommands {
+	/// Add a mod, resource pack or shader by its Modrinth slug, ID or URL, along with what it needs. A specific version can be given as `mod@version`
+	Add { instance: String, query: ModQuery },
+	/// Remove a mod, resource pack or shader, along with dependencies nothing else needs. Files stay in the store
+	Remove {
+		instance: String,
+		name: String,
+		/// Remove it even if other mods need it
+		#[clap(long)]
+		force: bool,
+	},
+	/// List an instance's mods, resource packs and shaders
+	List { instance: String },
+	/// Enable a disabled mod
+	Enable { instance: String, name: String },
+	/// Disable a mod, without removing it
+	Disable { instance: String, name: String },
+	/// Show newer compatible versions of an instance's mods, resource packs and shaders, with links to their changelogs
+	Outdated { instance: String },
+	/// Update one mod, or everything with `--all`. Pinned mods are held back
+	Update {
+		instance: String,
+		#[clap(required_unless_present = "all")]
+		name: Option<String>,
+		/// Update every mod, resource pack and shader that isn't pinned
+		#[clap(long, conflicts_with = "name")]
+		all: bool,
+	},
+	/// Hold a mod at its locked version when updating
+	Pin { instance: String, name: String },
+	/// Let a pinned mod be updated again
+	Unpin { instance: String, name: String },
+	/// Look up jars that were put in the mods folder by hand on Modrinth, and manage the ones that are found
+	Scan { instance: String },
+}
+
+#[derive(Subcommand)]
+enum InstanceCommands {
+	/// Create an instance of a specific version of Minecraft
+	Create {
+		name: String,
+		version: String,
+		/// Mod loader to install, as `loader[@version]` (e.g. `quilt`, `fabric@0.15.11` or `neoforge`). Without a version (or with `@stable`), the instance tracks the latest stable one
+		#[clap(long)]
+		loader: Option<Loader>,
+		/// Initial heap size (e.g. `512M`, `2G`)
+		#[clap(long)]
+		min_memory: Option<String>,
+		/// Maximum heap size (e.g. `4G`), or `auto` to size it from physical memory
+		#[clap(long)]
+		max_memory: Option<String>,
+		/// Garbage collector preset: g1, zgc or shenandoah
+		#[clap(long)]
+		gc: Option<GcPreset>,
+		/// Extra arguments to pass to Java
+		#[clap(long, allow_hyphen_values = true)]
+		jvm_arg: Vec<String>,
+		/// log4j config: profile, xml, plain, none, or a path to a config file (relative to the instance's directory)
+		#[clap(long, default_value = "profile")]
+		logging: LogConfig,
+		/// Command to run the game through, e.g. `gamemoderun`. Can be repeated, outermost first
+		#[clap(long)]
+		wrapper: Vec<String>,
+		/// Environment variable for the game, as `KEY=VALUE`. Can be repeated
+		#[clap(long)]
+		env: Vec<String>,
+	},
+}
+
+fn load_config(path: &Paths, name: &str) -> Result<InstanceConfig, Box<dyn Error + Send + Sync>> {
+	Ok(InstanceConfig::load(path, name)?.ok_or(format!("No instance named {name}"))?)
+}
+
+/// Load an instance, or a version of Minecraft with the default config if there's no instance called `name`.
+async fn load_instance(
+	path: &Paths,
+	name: &str,
+	logging: Option<LogConfig>,
+) -> Result<Instance, Box<dyn Error + Send + Sync>> {
+	let existing = InstanceConfig::load(path, name)?;
+	let is_instance = existing.is_some();
+	let mut config = existing.unwrap_or_else(|| InstanceConfig::new(name.to_string()));
+	if let Some(logging) = logging {
+		config.logging = logging;
+	}
+	let manifest = fetch_version_manifest().await?;
+	let version = manifest
+		.versions
+		.get(&config.version)
+		.ok_or(format!("Unknown version {}", config.version))?;
+	let mut profile = get_profile(version).await?;
+	if let Some(loader) = &config.loader {
+		profile = loader.install(path, profile).await?;
+	}
+	match is_instance {
+		true => Instance::new(path, name, config, profile),
+		false => Instance::version(path, config, profile),
+	}
+}
+
+fn print_command(command: &LaunchCommand) {
+	if !command.wrappers.is_empty() {
+		println!("Wrappers: {}", command.wrappers.join(" "));
+	}
+	println!("Java: {}", command.java.display());
+	println!("JVM arguments:");
+	let mut args = command.jvm_args.iter();
+	while let Some(arg) = args.next() {
+		if arg == "-cp" || arg == "-classpath" {
+			println!("  {arg}");
+			for entry in args
+				.next()
+				.into_iter()
+				.flat_map(|classpath| classpath.split(':'))
+			{
+				println!("    {entry}");
+			}
+		} else {
+			println!("  {arg}");
+		}
+	}
+	println!("Main class: {}", command.main_class);
+	println!("Game arguments:");
+	for arg in &command.game_args {
+		println!("  {arg}");
+	}
+	for (key, value) in &command.env {
+		println!("Environment: {key}={value}");
+	}
+}
+
+/// Switch an instance to an updated lockfile, fetching everything before changing any links, and log what changed.
+async fn apply_update(
+	directory: &Path,
+	lock: &Lockfile,
+	updated: &Lockfile,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+	updated.install(directory).await?;
+	save_url_cache()?;
+	updated.save(directory)?;
+
+	let mut changed = false;
+	for (slug, entry) in &updated.entries {
+		match lock.entries.get(slug) {
+			Some(old) if old.version_id == entry.version_id => continue,
+			Some(old) => info!(
+				"Updated {slug} {} -> {}",
+				old.version_number, entry.version_number
+			),
+			None => info!("Added {slug} {}", entry.version_number),
+		}
+		changed = true;
+	}
+	for (slug, old) in &lock.entries {
+		if !updated.entries.contains_key(slug) {
+			info!(
+				"Removed {slug} {}, nothing needs it anymore",
+				old.version_number
+			);
+			changed = true;
+		}
+	}
+	if !changed {
+		info!("Everything is up to date");
+	}
+	Ok(())
 }
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn Error + Send + Sync>> {
+async fn main() -> ExitCode {
 	// Initialize logging
 	tracing::subscriber::set_global_default(FmtSubscriber::default())
 		.expect("Failed to initialize logging!");
 
-	// Main init
-	let args = Args::parse();
+	// Errors are shown with their messages, which can span several lines (like dependency conflicts).
+	match run(Args::parse()).await {
+		Ok(()) => ExitCode::SUCCESS,
+		Err(err) => {
+			eprintln!("Error: {err}");
+			ExitCode::FAILURE
+		}
+	}
+}
+
+async fn run(args: Args) -> Result<(), Box<dyn Error + Send + Sync>> {
 	let path = Paths::new("copper_launcher".to_string())?;
 
 	info!("Config directory: {}", path.config.display());
 	info!("Cache directory: {}", path.cache.display());
 
 	match &args.command {
-		Commands::Launch { version } => {
+		Commands::Launch {
+			instance: name,
+			join,
+			world,
+			realm,
+			logging,
+			log_level,
+			logger,
+			log_json,
+			dry_run,
+			repair,
+		} => {
+			let quick_play = match (join, world, realm) {
+				(Some(address), _, _) => Some(QuickPlay::Multiplayer(address.clone())),
+				(_, Some(world), _) => Some(QuickPlay::Singleplayer(world.clone())),
+				(_, _, Some(realm)) => Some(QuickPlay::Realms(realm.clone())),
+				_ => None,
+			};
+
+			if *dry_run {
+				set_offline(true);
+			}
+			let mut instance = load_instance(&path, name, logging.clone()).await?;
+			instance.set_quick_play(quick_play)?;
+			instance.set_repair(*repair);
+			if *dry_run {
+				print_command(&instance.launch_command()?.redacted());
+				return Ok(());
+			}
+			let mut game = instance.launch().await?;
+			save_url_cache()?;
+			info!("Started {name} (PID {})", game.pid());
+			let mut printer = LogPrinter::new(
+				instance.emits_xml_logs(),
+				*log_level,
+				logger.clone(),
+				log_json.as_deref(),
+			)?;
+
+			// The first Ctrl+C asks the game to exit, the second one kills it.
+			let mut interrupts = 0;
+			loop {
+				tokio::select! {
+					event = game.next_event() => match event {
+						Some(GameEvent::Stdout(line)) => printer.stdout(&line)?,
+						Some(GameEvent::Stderr(line)) => printer.stderr(&line),
+						None => break,
+					},
+					_ = ctrl_c() => {
+						interrupts += 1;
+						if interrupts == 1 {
+							info!("Stopping {name}, press Ctrl+C again to kill it");
+							game.terminate()?;
+						} else {
+							game.kill().await?;
+						}
+					}
+				}
+			}
+
+			printer.finish()?;
+			let exit = game.wait().await?;
+			let runtime = exit.runtime.as_secs();
+			let runtime = format!(
+				"{}:{:02}:{:02}",
+				runtime / 3600,
+				runtime / 60 % 60,
+				runtime % 60
+			);
+			match (exit.code, exit.signal) {
+				(Some(0), _) => info!("{name} exited after {runtime}"),
+				(Some(code), _) => warn!("{name} exited with code {code} after {runtime}"),
+				(None, signal) => warn!(
+					"{name} was killed by signal {} after {runtime}",
+					signal.unwrap_or_default()
+				),
+			}
+
+			if let Err(err) = instance.post_exit(&exit).await {
+				warn!("{err}");
+			}
+
+			// Being killed by a signal (including our own Ctrl+C handling) isn't a crash.
+			if exit.code.is_some() && !exit.success() {
+				let analysis = instance.analyze_crash(&game)?;
+				eprintln!("\nCrash diagnosis: {}", analysis.cause);
+				eprintln!("{}", analysis.cause.description());
+				if let Some(source) = &analysis.source {
+					eprintln!("\nFrom {}:", source.display());
+				}
+				for line in &analysis.excerpt {
+					eprintln!("  {line}");
+				}
+				for report in &analysis.reports {
+					eprintln!("Crash report: {}", report.display());
+				}
+			}
+
+			if let Some(entry) = instance.quick_play_result(&game)? {
+				info!(
+					"Quick play: {} `{}` ({})",
+					entry.world.world_type, entry.world.name, entry.world.id
+				);
+			}
+		}
+		Commands::ExportScript { instance, output } => {
+			let mut instance = load_instance(&path, instance, None).await?;
+			let output = output
+				.clone()
+				.unwrap_or_else(|| instance.directory().join("launch.sh"));
+			instance.export_script(&output).await?;
+			save_url_cache()?;
+			info!("Wrote launch script to {}", output.display());
+		}
+		Commands::Deobf {
+			report,
+			version,
+			instance,
+			server,
+			output,
+		} => {
+			let text = fs::read_to_string(report)?;
+			let version = match (version, instance) {
+				(Some(version), _) => version.clone(),
+				(None, Some(instance)) => load_config(&path, instance)?.version,
+				(None, None) => Mappings::report_version(&text)
+					.ok_or("Couldn't find the Minecraft version in the report, pass --version")?
+					.to_string(),
+			};
+
 			let manifest = fetch_version_manifest().await?;
-			let instance = Instance::new(
-				&path,
-				get_profile(manifest.versions.get(version).unwrap())
-					.await
-					.unwrap(),
-			)
-			.await?;
-			instance.launch().await?;
+			let entry = manifest
+				.versions
+				.get(&version)
+				.ok_or(format!("Unknown version {version}"))?;
+			let profile = get_profile(entry).await?;
+			let download = match server {
+				false => profile.downloads.client_mappings.as_ref(),
+				true => profile.downloads.server_mappings.as_ref(),
+			}
+			.ok_or(format!(
+				"Mojang doesn't publish mappings for {version} (only 1.14.4 and newer)"
+			))?;
+			let mappings = Mappings::fetch(download).await?;
+			save_url_cache()?;
+
+			let deobfuscated = mappings.deobfuscate(&text);
+			match output {
+				Some(output) => fs::write(output, deobfuscated + "\n")?,
+				None => println!("{deobfuscated}"),
+			}
+		}
+		Commands::Instance { command } => match command {
+			InstanceCommands::Create {
+				name,
+				version,
+				loader,
+				min_memory,
+				max_memory,
+				gc,
+				jvm_arg,
+				logging,
+				wrapper,
+				env,
+			} => {
+				if InstanceConfig::load(&path, name)?.is_some() {
+					return Err(format!("Instance {name} already exists").into());
+				}
+				let manifest = fetch_version_manifest().await?;
+				if !manifest.versions.contains_key(version) {
+					return Err(format!("Unknown version {version}").into());
+				}
+
+				let mut config = InstanceConfig::new(version.clone());
+				if let Some(loader) = loader {
+					// Check the loader supports this version now, rather than when the instance is launched.
+					let loader_version = loader.resolve_version(version).await?;
+					info!(
+						"{} {loader_version} supports Minecraft {version}",
+						loader.kind
+					);
+					config.loader = Some(loader.clone());
+				}
+				if let Some(min_memory) = min_memory {
+					config.jvm.min_memory = Some(parse_memory(min_memory)?);
+				}
+				match max_memory.as_deref() {
+					Some("auto") => config.jvm.auto_memory = true,
+					Some(max_memory) => config.jvm.max_memory = Some(parse_memory(max_memory)?),
+					None => {}
+				}
+				config.jvm.gc = *gc;
+				config.jvm.extra_args = jvm_arg.clone();
+				config.logging = logging.clone();
+				config.wrappers = wrapper.clone();
+				for variable in env {
+					let (key, value) = variable
+						.split_once('=')
+						.ok_or(format!("Expected KEY=VALUE, got `{variable}`"))?;
+					config.env.insert(key.to_string(), value.to_string());
+				}
+				config.save(&path, name)?;
+				info!(
+					"Created instance {name} in {}",
+					InstanceConfig::directory(&path, name).display()
+				);
+			}
+		},
+		Commands::Mod { command } => match command {
+			ModCommands::Add { instance, query } => {
+				let config = load_config(&path, instance)?;
+				let directory = InstanceConfig::directory(&path, instance);
+				let lock = Lockfile::load(&directory)?;
+				let modrinth = Modrinth::default();
+				let provider =
+					ModrinthProvider::new(&modrinth, &config.version, config.loader.as_ref());
+				let (project, _) = provider.project(&query.project).await?;
+				let request = Dependency {
+					project: project.id.clone(),
+					version: query.version.clone(),
+					kind: DependencyKind::Required,
+				};
+				let installed = provider.installed(&lock).await?;
+				let resolution = resolve_with(&provider, &[request], &installed).await?;
+
+				let mut updated = lock.clone();
+				for candidate in &resolution.install {
+					let mut entry = candidate.data.clone();
+					let old = lock.entries.get(&candidate.name);
+					entry.dependency =
+						candidate.project != project.id && old.is_none_or(|old| old.dependency);
+					updated.entries.insert(candidate.name.clone(), entry);
+				}
+				// Replaced versions' links are removed by installing, since they may have different file names.
+				updated.install(&directory).await?;
+				save_url_cache()?;
+				updated.save(&directory)?;
+				for candidate in &resolution.install {
+					info!("Added {candidate}");
+				}
+				for suggestion in &resolution.suggestions {
+					// Optional dependencies aren't fetched while resolving, so they're only known by ID until now.
+					let slug = match provider.project(&suggestion.project).await {
+						Ok((project, _)) => project.slug,
+						Err(_) => suggestion.name.clone(),
+					};
+					info!(
+						"{} suggests {slug}, add it with `copper mod add {instance} {slug}`",
+						suggestion.by
+					);
+				}
+			}
+			ModCommands::Remove {
+				instance,
+				name,
+				force,
+			} => {
+				let config = load_config(&path, instance)?;
+				let directory = InstanceConfig::directory(&path, instance);
+				let lock = Lockfile::load(&directory)?;
+				let slug = lock.find(name)?;
+				let modrinth = Modrinth::default();
+				let provider =
+					ModrinthProvider::new(&modrinth, &config.version, config.loader.as_ref());
+				let mut installed = provider.installed(&lock).await?;
+				let dependents = mods::dependents(&installed, &lock.entries[&slug].project_id);
+				if !dependents.is_empty() && !force {
+					return Err(Box::new(ModError::Needed {
+						name: slug,
+						by: dependents,
+					}));
+				}
+				if !dependents.is_empty() {
+					warn!(
+						"{slug} is needed by {}, removing it anyway",
+						dependents.join(", ")
+					);
+				}
+
+				// Dependencies are removed too once nothing that was added directly needs them.
+				let mut updated = lock.clone();
+				updated.entries.remove(&slug);
+				installed.retain(|candidate| candidate.name != slug);
+				let needed = mods::dependency_closure(
+					&installed,
+					updated
+						.entries
+						.iter()
+						.filter(|(_, entry)| !entry.dependency)
+						.map(|(slug, _)| slug.as_str()),
+				);
+				updated
+					.entries
+					.retain(|slug, entry| !entry.dependency || needed.contains(slug));
+				updated.install(&directory).await?;
+				updated.save(&directory)?;
+
+				info!("Removed {slug}");
+				for orphan in lock.entries.keys() {
+					if orphan != &slug && !updated.entries.contains_key(orphan) {
+						info!("Removed {orphan}, nothing needs it anymore");
+					}
+				}
+			}
+			ModCommands::List { instance } => {
+				load_config(&path, instance)?;
+				let directory = InstanceConfig::directory(&path, instance);
+				let lock = Lockfile::load(&directory)?;
+				for (slug, entry) in &lock.entries {
+					let mut notes = Vec::new();
+					if entry.kind != ContentKind::Mod {
+						notes.push(entry.kind.directory());
+					}
+					if entry.dependency {
+						notes.push("dependency");
+					}
+					if !entry.enabled {
+						notes.push("disabled");
+					}
+					if entry.pinned {
+						notes.push("pinned");
+					}
+					match notes.is_empty() {
+						true => println!("{slug} {}", entry.version_number),
+						false => println!("{slug} {} ({})", entry.version_number, notes.join(", ")),
+					}
+				}
+				for jar in mods::unmanaged(&directory, &lock)? {
+					println!("{} (unmanaged)", jar.file_name().unwrap().to_string_lossy());
+				}
+			}
+			ModCommands::Enable { instance, name } | ModCommands::Disable { instance, name } => {
+				let enabled = matches!(command, ModCommands::Enable { .. });
+				load_config(&path, instance)?;
+				let directory = InstanceConfig::directory(&path, instance);
+				let mut lock = Lockfile::load(&directory)?;
+				let slug = lock.find(name)?;
+				let entry = lock.entries.get_mut(&slug).unwrap();
+				entry.enabled = enabled;
+				entry.link(&directory).await?;
+				save_url_cache()?;
+				lock.save(&directory)?;
+				match enabled {
+					true => info!("Enabled {slug}"),
+					false => info!("Disabled {slug}"),
+				}
+			}
+			ModCommands::Outdated { instance } => {
+				let config = load_config(&path, instance)?;
+				let directory = InstanceConfig::directory(&path, instance);
+				let lock = Lockfile::load(&directory)?;
+				let modrinth = Modrinth::default();
+				let provider =
+					ModrinthProvider::new(&modrinth, &config.version, config.loader.as_ref());
+				// Pins are ignored here, so pinned entries show what they're being held back from.
+				let mut newest = BTreeMap::new();
+				let mut conflicts = Vec::new();
+				match mods::resolve_update(&provider, &lock, |_, _| true).await {
+					Ok(updated) => newest = updated.entries,
+					// When everything can't be updated together, each mod is checked on its own (with the dependencies it
+					// needs), so one conflict doesn't hide every other update.
+					Err(error) if error.is::<ResolveError>() => {
+						let installed = provider.installed(&lock).await?;
+						for (slug, entry) in &lock.entries {
+							if entry.dependency {
+								continue;
+							}
+							let scope = mods::update_scope(&installed, &lock, slug);
+							let updated = mods::resolve_update(&provider, &lock, |slug, _| {
+								scope.contains(slug)
+							})
+							.await;
+							match updated {
+								Ok(updated) => {
+									for slug in &scope {
+										if let Some(entry) = updated.entries.get(slug) {
+											newest.entry(slug.clone()).or_insert(entry.clone());
+										}
+									}
+								}
+								Err(error) if error.is::<ResolveError>() => {
+									conflicts.push((slug.clone(), error))
+								}
+								Err(error) => return Err(error),
+							}
+						}
+					}
+					Err(error) => return Err(error),
+				}
+
+				let mut rows = vec![[
+					"Name".to_string(),
+					"Locked".to_string(),
+					"Latest".to_string(),
+					"Changelog".to_string(),
+				]];
+				for (slug, old) in &lock.entries {
+					if conflicts.iter().any(|(conflict, _)| conflict == slug) {
+						rows.push([
+							slug.clone(),
+							old.version_number.clone(),
+							"(conflict)".to_string(),
+							String::new(),
+						]);
+						continue;
+					}
+					let Some(new) = newest.get(slug) else {
+						continue;
+					};
+					if new.version_id == old.version_id {
+						continue;
+					}
+					let latest = match old.pinned {
+						true => format!("{} (pinned)", new.version_number),
+						false => new.version_number.clone(),
+					};
+					rows.push([
+						slug.clone(),
+						old.version_number.clone(),
+						latest,
+						new.page_url(slug),
+					]);
+				}
+				if rows.len() == 1 {
+					info!("Everything is up to date");
+				} else {
+					let mut widths = [0; 3];
+					for row in &rows {
+						for (width, cell) in widths.iter_mut().zip(row) {
+							*width = (*width).max(cell.len());
+						}
+					}
+					for [name, locked, latest, changelog] in &rows {
+						println!(
+							"{name:<0$}  {locked:<1$}  {latest:<2$}  {changelog}",
+							widths[0], widths[1], widths[2]
+						);
+					}
+				}
+				for (slug, error) in &conflicts {
+					warn!("{slug} can't be updated: {error}");
+				}
+			}
+			ModCommands::Update { instance, name, .. } => {
+				let config = load_config(&path, instance)?;
+				let directory = InstanceConfig::directory(&path, instance);
+				let lock = Lockfile::load(&directory)?;
+				let only = name.as_deref().map(|name| lock.find(name)).transpose()?;
+				// Pinned entries are held when updating, so naming one would quietly do nothing.
+				if let Some(only) = &only {
+					let entry = &lock.entries[only];
+					if entry.pinned {
+						let version = entry.version_number.clone();
+						return Err(Box::new(ModError::Pinned(only.clone(), version)));
+					}
+				}
+				let modrinth = Modrinth::default();
+				let provider =
+					ModrinthProvider::new(&modrinth, &config.version, config.loader.as_ref());
+				// Updating one mod updates the dependencies it needs too, since they weren't added directly.
+				let scope = match &only {
+					Some(only) => {
+						let installed = provider.installed(&lock).await?;
+						Some(mods::update_scope(&installed, &lock, only))
+					}
+					None => None,
+				};
+				let updated = mods::resolve_update(&provider, &lock, |slug, entry| {
+					!entry.pinned && scope.as_ref().is_none_or(|scope| scope.contains(slug))
+				})
+				.await?;
+				apply_update(&directory, &lock, &updated).await?;
+			}
+			ModCommands::Pin { instance, name } | ModCommands::Unpin { instance, name } => {
+				let pinned = matches!(command, ModCommands::Pin { .. });
+				load_config(&path, instance)?;
+				let directory = InstanceConfig::directory(&path, instance);
+				let mut lock = Lockfile::load(&directory)?;
+				let slug = lock.find(name)?;
+				let entry = lock.entries.get_mut(&slug).unwrap();
+				entry.pinned = pinned;
+				match pinned {
+					true => info!("Pinned {slug} at {}", entry.version_number),
+					false => info!("Unpinned {slug}"),
+				}
+				lock.save(&directory)?;
+			}
+			ModCommands::Scan { instance } => {
+				let config = load_config(&path, instance)?;
+				let directory = InstanceConfig::directory(&path, instance);
+				let loader = config.loader.as_ref().ok_or(ModError::NoLoader)?;
+				let mut lock = Lockfile::load(&directory)?;
+				let scanned = mods::scan(
+					&Modrinth::default(),
+					&directory,
+					&config.version,
+					loader,
+					&mut lock,
+				)
+				.await?;
+
+				for (jar, result) in &scanned {
+					let jar = jar.file_name().unwrap().to_string_lossy();
+					match result {
+						Scanned::Locked(slug) => {
+							info!("{jar} is {slug} {}", lock.entries[slug].version_number)
+						}
+						Scanned::Duplicate(slug) => {
+							warn!("{jar} is another version of {slug}, which is already installed")
+						}
+						Scanned::Incompatible { slug, version } => warn!(
+							"{jar} is {slug} {}, which is for Minecraft {} on {}, so it was left alone",
+							version.version_number,
+							version.game_versions.join(", "),
+							version.loaders.join(", ")
+						),
+						Scanned::Unknown(Some(metadata)) => {
+							warn!("{jar} isn't on Modrinth, it says it's {metadata}")
+						}
+						Scanned::Unknown(None) => {
+							warn!("{jar} isn't on Modrinth, and doesn't say what mod it is")
+						}
+					}
+				}
+				if scanned.is_empty() {
+					info!("Every jar in mods is already managed");
+				}
+			}
+		},
+		Commands::Install { instance } => {
+			load_config(&path, instance)?;
+			let directory = InstanceConfig::directory(&path, instance);
+			let lock = Lockfile::load(&directory)?;
+			lock.install(&directory).await?;
 			save_url_cache()?;
+			info!("Installed {} files from {LOCK_FILE}", lock.entries.len());
+		}
+		Commands::Update { instance } => {
+			let config = load_config(&path, instance)?;
+			let directory = InstanceConfig::directory(&path, instance);
+			let lock = Lockfile::load(&directory)?;
+			let modrinth = Modrinth::default();
+			let provider =
+				ModrinthProvider::new(&modrinth, &config.version, config.loader.as_ref());
+			let updated = mods::resolve_update(&provider, &lock, |_, entry| !entry.pinned).await?;
+			apply_update(&directory, &lock, &updated).await?;
 		}
 	}
 
@@ -0,0 +1,82 @@
+//! Fabric's meta API, which lists loader versions and serves version profiles for them.
+//!
+//! See <https://github.com/FabricMC/fabric-meta>.
+
+use crate::{
+	store::{fetch_data, fetch_fresh_data},
+	structs::PartialProfile,
+	Result,
+};
+use serde::{Deserialize, Serialize};
+use std::env;
+
+pub const META_URL: &str = "https://meta.fabricmc.net/v2";
+/// Environment variable that overrides [META_URL], for mirrors or a local server.
+pub const META_URL_VARIABLE: &str = "COPPER_FABRIC_META_URL";
+
+/// A version of a component (like the loader, or intermediary mappings) in the meta API.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct MetaVersion {
+	pub version: String,
+	/// Maven coordinate of the component.
+	pub maven: String,
+	/// Not every meta service marks versions as stable (Quilt's doesn't), so this is only meaningful when it's given.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub stable: Option<bool>,
+}
+
+impl MetaVersion {
+	/// Whether this is a stable release. Versions that aren't marked either way are stable unless they have a pre-release suffix, like `0.20.0-beta.9`.
+	pub fn is_stable(&self) -> bool {
+		self.stable.unwrap_or_else(|| !self.version.contains('-'))
+	}
+}
+
+/// A loader version that works with a specific game version.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct LoaderEntry {
+	pub loader: MetaVersion,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub intermediary: Option<MetaVersion>,
+}
+
+/// A meta API client. The base URL can be changed, for mirrors or a local server, see [META_URL_VARIABLE].
+#[derive(Clone, Debug)]
+pub struct FabricMeta {
+	pub url: String,
+}
+
+impl Default for FabricMeta {
+	fn default() -> Self {
+		Self::new(env::var(META_URL_VARIABLE).unwrap_or_else(|_| META_URL.to_string()))
+	}
+}
+
+impl FabricMeta {
+	pub fn new(url: impl Into<String>) -> Self {
+		Self { url: url.into() }
+	}
+
+	fn endpoint(&self, path: &str) -> String {
+		format!("{}/{path}", self.url.trim_end_matches('/'))
+	}
+
+	/// Loader versions that work with a game version, newest first. Always fetched fresh (unless downloads are turned off), since new versions come out often.
+	pub async fn loader_versions(&self, game_version: &str) -> Result<Vec<LoaderEntry>> {
+		let url = self.endpoint(&format!("versions/loader/{game_version}"));
+		fetch_fresh_data(url).await
+	}
+
+	/// The version profile for a loader version, which inherits from the game version.
+	/// These never change once published, so they're cached in the store.
+	pub async fn profile(
+		&self,
+		game_version: &str,
+		loader_version: &str,
+	) -> Result<PartialProfile> {
+		let url = self.endpoint(&format!(
+			"versions/loader/{game_version}/{loader_version}/profile/json"
+		));
+		fetch_data(None, url).await
+	}
+}
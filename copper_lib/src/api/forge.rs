@@ -0,0 +1,79 @@
+//! Finding Forge and NeoForge versions, and their installers.
+
+use super::maven::MavenRepository;
+use crate::{store::fetch_fresh_data, structs::MavenCoordinate, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+pub const FORGE_MAVEN: &str = "https://maven.minecraftforge.net/";
+pub const FORGE_PROMOTIONS_URL: &str =
+	"https://files.minecraftforge.net/net/minecraftforge/forge/promotions_slim.json";
+pub const NEOFORGE_MAVEN: &str = "https://maven.neoforged.net/releases/";
+pub const NEOFORGE_VERSIONS_URL: &str =
+	"https://maven.neoforged.net/api/maven/versions/releases/net/neoforged/neoforge";
+
+/// Forge's recommended and latest versions for each Minecraft version, keyed like `1.20.1-recommended`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Promotions {
+	pub promos: HashMap<String, String>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct NeoForgeVersions {
+	/// Oldest first.
+	pub versions: Vec<String>,
+}
+
+/// Fetch Forge's promotions. Always fetched fresh (unless downloads are turned off), since they change with every release.
+pub async fn fetch_promotions() -> Result<Promotions> {
+	fetch_fresh_data(FORGE_PROMOTIONS_URL.to_string()).await
+}
+
+/// The recommended Forge version for a Minecraft version, or the latest if none is recommended yet.
+pub async fn forge_version(game_version: &str) -> Result<Option<String>> {
+	let promotions = fetch_promotions().await?;
+	Ok(promotions
+		.promos
+		.get(&format!("{game_version}-recommended"))
+		.or_else(|| promotions.promos.get(&format!("{game_version}-latest")))
+		.cloned())
+}
+
+/// NeoForge versions for a Minecraft version, oldest first. NeoForge's versions start with Minecraft's minor and patch versions, so 1.20.4 is `20.4.*`.
+pub async fn neoforge_versions(game_version: &str) -> Result<Vec<String>> {
+	let mut parts = game_version.split('.').skip(1);
+	let prefix = match (parts.next(), parts.next()) {
+		(Some(minor), Some(patch)) => format!("{minor}.{patch}."),
+		(Some(minor), None) => format!("{minor}.0."),
+		_ => return Ok(Vec::new()),
+	};
+	let versions: NeoForgeVersions = fetch_fresh_data(NEOFORGE_VERSIONS_URL.to_string()).await?;
+	Ok(versions
+		.versions
+		.into_iter()
+		.filter(|version| version.starts_with(&prefix))
+		.collect())
+}
+
+/// Where to find a Forge installer. Forge's versions are prefixed with the Minecraft version they're for.
+pub fn forge_installer(game_version: &str, version: &str) -> (MavenRepository, MavenCoordinate) {
+	let coordinate = MavenCoordinate {
+		group: "net.minecraftforge".into(),
+		artifact: "forge".into(),
+		version: format!("{game_version}-{version}"),
+		classifier: Some("installer".into()),
+		extension: "jar".into(),
+	};
+	(MavenRepository::new(FORGE_MAVEN), coordinate)
+}
+
+pub fn neoforge_installer(version: &str) -> (MavenRepository, MavenCoordinate) {
+	let coordinate = MavenCoordinate {
+		group: "net.neoforged".into(),
+		artifact: "neoforge".into(),
+		version: version.into(),
+		classifier: Some("installer".into()),
+		extension: "jar".into(),
+	};
+	(MavenRepository::new(NEOFORGE_MAVEN), coordinate)
+}
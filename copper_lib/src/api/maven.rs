@@ -0,0 +1,106 @@
+//! Fetching artifacts from Maven repositories, for libraries that only have a name and a repository URL.
+
+use crate::{
+	store::{check_online, get, save, StoreInput, StoreOutput},
+	structs::maven::MavenCoordinate,
+	Result,
+};
+use data_encoding::HEXLOWER;
+use reqwest::StatusCode;
+use sha1::Sha1;
+use sha2::{Digest, Sha512};
+use std::{error::Error, fmt};
+use tracing::warn;
+
+/// Mojang's library repository, used for libraries that don't say where they're from.
+pub const MOJANG_LIBRARIES: &str = "https://libraries.minecraft.net/";
+
+#[derive(Debug)]
+pub enum MavenError {
+	NotFound(String),
+	ChecksumMismatch {
+		url: String,
+		algorithm: &'static str,
+	},
+}
+impl fmt::Display for MavenError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::NotFound(url) => write!(f, "{url} wasn't found"),
+			Self::ChecksumMismatch { url, algorithm } => {
+				write!(f, "{url} doesn't match its {algorithm} checksum")
+			}
+		}
+	}
+}
+impl Error for MavenError {}
+
+/// A Maven repository, such as <https://maven.fabricmc.net/>.
+#[derive(Clone, Debug)]
+pub struct MavenRepository {
+	pub url: String,
+}
+
+impl MavenRepository {
+	pub fn new(url: impl Into<String>) -> Self {
+		Self { url: url.into() }
+	}
+
+	pub fn url(&self, coordinate: &MavenCoordinate) -> String {
+		coordinate.url(&self.url)
+	}
+
+	/// Fetch an artifact into the store, checking it against the repository's `.sha512` or `.sha1` checksum the first time it's downloaded.
+	pub async fn fetch(&self, coordinate: &MavenCoordinate) -> Result<StoreOutput> {
+		let url = self.url(coordinate);
+		if let Ok(store) = get(StoreInput::URL(url.clone())) {
+			return Ok(store);
+		}
+
+		check_online(&url)?;
+		let response = reqwest::get(&url).await?;
+		if response.status() == StatusCode::NOT_FOUND {
+			return Err(Box::new(MavenError::NotFound(url)));
+		}
+		let data = response.error_for_status()?.bytes().await?;
+
+		if let Some(sha512) = checksum(&format!("{url}.sha512")).await? {
+			let mut hasher = Sha512::new();
+			hasher.update(&data);
+			if HEXLOWER.encode(&hasher.finalize()) != sha512 {
+				return Err(Box::new(MavenError::ChecksumMismatch {
+					url,
+					algorithm: "sha512",
+				}));
+			}
+		} else if let Some(sha1) = checksum(&format!("{url}.sha1")).await? {
+			let mut hasher = Sha1::new();
+			hasher.update(&data);
+			if HEXLOWER.encode(&hasher.finalize()) != sha1 {
+				return Err(Box::new(MavenError::ChecksumMismatch {
+					url,
+					algorithm: "sha1",
+				}));
+			}
+		} else {
+			warn!("{url} has no checksum, using it unverified");
+		}
+
+		save(&data, StoreInput::URL(url))
+	}
+}
+
+/// Fetch a checksum file into the store, or `None` if the repository doesn't have one.
+async fn checksum(url: &str) -> Result<Option<String>> {
+	let response = reqwest::get(url).await?;
+	if !response.status().is_success() {
+		return Ok(None);
+	}
+	let data = response.bytes().await?;
+	save(&data, StoreInput::URL(url.to_string()))?;
+	// Some repositories follow the checksum with the file name, like `sha1sum` does.
+	Ok(String::from_utf8_lossy(&data)
+		.split_whitespace()
+		.next()
+		.map(str::to_lowercase))
+}
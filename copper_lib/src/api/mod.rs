@@ -1,3 +1,8 @@
 //! Module for fetching and deserialing data from APIs, such as Mojang, Modrinth, or Curseforge.
 
+pub mod fabric;
+pub mod forge;
+pub mod maven;
+pub mod modrinth;
 pub mod mojang;
+pub mod quilt;
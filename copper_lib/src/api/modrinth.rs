@@ -0,0 +1,319 @@
+//! Modrinth's API, for finding mods, resource packs and shaders, and identifying files by hash.
+//!
+//! See <https://docs.modrinth.com/api/>.
+
+use crate::{
+	store::{fetch_verified, StoreOutput},
+	Result,
+};
+use reqwest::{Client, RequestBuilder, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, env};
+
+pub const API_URL: &str = "https://api.modrinth.com/v2";
+/// Modrinth's website, for linking to projects and versions.
+pub const SITE_URL: &str = "https://modrinth.com";
+/// Environment variable that overrides [API_URL], for Modrinth's staging server or a local one.
+pub const API_URL_VARIABLE: &str = "COPPER_MODRINTH_API_URL";
+/// Modrinth requires a User-Agent identifying the app, and blocks generic ones.
+pub const USER_AGENT: &str = concat!("obj-obj/copper_rs/", env!("CARGO_PKG_VERSION"));
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProjectType {
+	Mod,
+	Modpack,
+	Resourcepack,
+	Shader,
+	Datapack,
+	Plugin,
+}
+
+/// Whether a project works on, or is needed on, one side.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SideSupport {
+	Required,
+	Optional,
+	Unsupported,
+	Unknown,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Project {
+	pub id: String,
+	pub slug: String,
+	pub title: String,
+	pub description: String,
+	pub project_type: ProjectType,
+	pub client_side: SideSupport,
+	pub server_side: SideSupport,
+	/// Categories, not including loaders.
+	#[serde(default)]
+	pub categories: Vec<String>,
+	#[serde(default)]
+	pub game_versions: Vec<String>,
+	#[serde(default)]
+	pub loaders: Vec<String>,
+	/// IDs of the project's versions.
+	#[serde(default)]
+	pub versions: Vec<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VersionType {
+	Release,
+	Beta,
+	Alpha,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Version {
+	pub id: String,
+	pub project_id: String,
+	pub name: String,
+	pub version_number: String,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub changelog: Option<String>,
+	pub date_published: String,
+	pub version_type: VersionType,
+	pub game_versions: Vec<String>,
+	pub loaders: Vec<String>,
+	#[serde(default)]
+	pub dependencies: Vec<Dependency>,
+	pub files: Vec<VersionFile>,
+}
+
+impl Version {
+	/// The version's primary file, or its first one if none is marked primary.
+	pub fn primary_file(&self) -> Option<&VersionFile> {
+		self.files
+			.iter()
+			.find(|file| file.primary)
+			.or_else(|| self.files.first())
+	}
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DependencyType {
+	Required,
+	Optional,
+	Incompatible,
+	/// Included in the dependent's jar, so it doesn't need installing.
+	Embedded,
+}
+
+/// A dependency on a project, or a specific version of one. Dependencies on files outside Modrinth only have a file name.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Dependency {
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub version_id: Option<String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub project_id: Option<String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub file_name: Option<String>,
+	pub dependency_type: DependencyType,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Hashes {
+	pub sha1: String,
+	pub sha512: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct VersionFile {
+	pub hashes: Hashes,
+	pub url: String,
+	pub filename: String,
+	pub primary: bool,
+	pub size: u64,
+}
+
+impl VersionFile {
+	/// Fetch the file into the store, checking its sha512. Files with the same sha512 are never downloaded twice.
+	pub async fn fetch(&self) -> Result<StoreOutput> {
+		fetch_verified(self.hashes.sha512.clone(), self.url.clone()).await
+	}
+}
+
+/// Filters for [Modrinth::search]. Every filter has to match; values within `categories` all have to match too.
+#[derive(Clone, Debug, Default)]
+pub struct Facets {
+	pub game_version: Option<String>,
+	pub loader: Option<String>,
+	pub categories: Vec<String>,
+	pub project_type: Option<ProjectType>,
+}
+
+impl Facets {
+	/// Facets as Modrinth expects them: a JSON list of lists, where the inner lists are OR'd and the outer one is AND'd.
+	pub fn to_json(&self) -> Result<String> {
+		let mut facets = Vec::new();
+		if let Some(game_version) = &self.game_version {
+			facets.push(vec![format!("versions:{game_version}")]);
+		}
+		// Loaders are categories in Modrinth's search.
+		if let Some(loader) = &self.loader {
+			facets.push(vec![format!("categories:{loader}")]);
+		}
+		for category in &self.categories {
+			facets.push(vec![format!("categories:{category}")]);
+		}
+		if let Some(project_type) = &self.project_type {
+			facets.push(vec![format!(
+				"project_type:{}",
+				serde_json::to_value(project_type)?.as_str().unwrap()
+			)]);
+		}
+		Ok(serde_json::to_string(&facets)?)
+	}
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SearchResults {
+	pub hits: Vec<SearchHit>,
+	pub offset: usize,
+	pub limit: usize,
+	pub total_hits: usize,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SearchHit {
+	pub project_id: String,
+	pub slug: String,
+	pub title: String,
+	pub description: String,
+	pub project_type: ProjectType,
+	pub author: String,
+	#[serde(default)]
+	pub categories: Vec<String>,
+	/// Game versions the project supports.
+	#[serde(default)]
+	pub versions: Vec<String>,
+	pub downloads: u64,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub latest_version: Option<String>,
+}
+
+#[derive(Serialize)]
+struct HashesRequest<'a> {
+	hashes: &'a [String],
+	algorithm: &'static str,
+}
+
+/// A Modrinth API client. The base URL can be changed, see [API_URL_VARIABLE].
+/// Responses aren't cached in the store, since projects and their versions change, but the files they point to are.
+#[derive(Clone, Debug)]
+pub struct Modrinth {
+	pub url: String,
+	client: Client,
+}
+
+impl Default for Modrinth {
+	fn default() -> Self {
+		Self::new(env::var(API_URL_VARIABLE).unwrap_or_else(|_| API_URL.to_string()))
+	}
+}
+
+impl Modrinth {
+	pub fn new(url: impl Into<String>) -> Self {
+		Self {
+			url: url.into(),
+			client: Client::builder()
+				.user_agent(USER_AGENT)
+				.build()
+				.expect("the TLS backend should be available"),
+		}
+	}
+
+	fn get(&self, path: &str) -> RequestBuilder {
+		self.client
+			.get(format!("{}/{path}", self.url.trim_end_matches('/')))
+	}
+
+	/// Search for projects, most relevant first.
+	pub async fn search(
+		&self,
+		query: &str,
+		facets: &Facets,
+		limit: usize,
+		offset: usize,
+	) -> Result<SearchResults> {
+		let request = self.get("search").query(&[
+			("query", query.to_string()),
+			("facets", facets.to_json()?),
+			("limit", limit.to_string()),
+			("offset", offset.to_string()),
+		]);
+		Ok(request.send().await?.error_for_status()?.json().await?)
+	}
+
+	/// Look up a project by its ID or slug.
+	pub async fn project(&self, id: &str) -> Result<Option<Project>> {
+		let response = self.get(&format!("project/{id}")).send().await?;
+		if response.status() == StatusCode::NOT_FOUND {
+			return Ok(None);
+		}
+		Ok(Some(response.error_for_status()?.json().await?))
+	}
+
+	/// A project's versions, newest first. Empty filters aren't applied.
+	pub async fn versions(
+		&self,
+		project: &str,
+		game_versions: &[String],
+		loaders: &[String],
+	) -> Result<Vec<Version>> {
+		let mut query = Vec::new();
+		if !game_versions.is_empty() {
+			query.push(("game_versions", serde_json::to_string(game_versions)?));
+		}
+		if !loaders.is_empty() {
+			query.push(("loaders", serde_json::to_string(loaders)?));
+		}
+		let request = self
+			.get(&format!("project/{project}/version"))
+			.query(&query);
+		Ok(request.send().await?.error_for_status()?.json().await?)
+	}
+
+	/// Look up a version by its ID.
+	pub async fn version(&self, id: &str) -> Result<Option<Version>> {
+		let response = self.get(&format!("version/{id}")).send().await?;
+		if response.status() == StatusCode::NOT_FOUND {
+			return Ok(None);
+		}
+		Ok(Some(response.error_for_status()?.json().await?))
+	}
+
+	/// Look up several versions by their IDs. Versions that don't exist are left out.
+	pub async fn versions_by_ids(&self, ids: &[String]) -> Result<Vec<Version>> {
+		if ids.is_empty() {
+			return Ok(Vec::new());
+		}
+		let request = self
+			.get("versions")
+			.query(&[("ids", serde_json::to_string(ids)?)]);
+		Ok(request.send().await?.error_for_status()?.json().await?)
+	}
+
+	/// Look up the versions that files belong to, by their sha512.
+	/// The store is keyed by sha512 too, so anything in it can be identified without reading or downloading it.
+	/// Files that aren't on Modrinth are missing from the result.
+	pub async fn versions_by_hashes(&self, hashes: &[String]) -> Result<HashMap<String, Version>> {
+		if hashes.is_empty() {
+			return Ok(HashMap::new());
+		}
+		let request = self
+			.client
+			.post(format!("{}/version_files", self.url.trim_end_matches('/')))
+			.json(&HashesRequest {
+				hashes,
+				algorithm: "sha512",
+			});
+		Ok(request.send().await?.error_for_status()?.json().await?)
+	}
+}
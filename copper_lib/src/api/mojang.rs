@@ -1,12 +1,16 @@
 use crate::{
-	store::{fetch, fetch_data, force_update},
-	structs::{profile::Download, version_manifest, AssetIndex, Profile, VersionManifest},
+	store::{fetch, fetch_data, fetch_fresh_data, fetch_sha1, force_update},
+	structs::{
+		java_runtime::RuntimeManifest, partial_profile::PartialProfileError, profile::Download,
+		version_manifest, AssetIndex, JavaRuntimes, PartialProfile, Profile, VersionManifest,
+	},
 	Result,
 };
 use chrono::DateTime;
-use std::{fs::File, time::UNIX_EPOCH};
+use std::{collections::HashSet, fs::File, time::UNIX_EPOCH};
 
 const MANIFEST_URL: &str = "https://launchermeta.mojang.com/mc/game/version_manifest.json";
+const JAVA_RUNTIMES_URL: &str = "https://launchermeta.mojang.com/v1/products/java-runtime/2ec0cc96c44e5a76b9c8b7c39df7210883d12871/all.json";
 
 pub async fn get_profile(version: &version_manifest::Entry) -> Result<Profile> {
 	if let Ok(cache_file) = File::open(fetch(None, version.url.clone()).await?.path) {
@@ -32,6 +36,53 @@ pub async fn get_profile(version: &version_manifest::Entry) -> Result<Profile> {
 	}
 }
 
+/// Resolve a profile's `inheritsFrom` chain into a complete profile.
+/// Parents are looked for in `known` first (for chains like OptiFine -> Forge -> vanilla), then in `complete`, then in the version manifest.
+pub async fn resolve_profile(
+	profile: PartialProfile,
+	known: &[PartialProfile],
+	complete: &[Profile],
+) -> Result<Profile> {
+	let mut seen = HashSet::from([profile.id.clone()]);
+	let mut chain = vec![profile];
+	let mut base = None;
+
+	while let Some(parent) = chain.last().unwrap().inherits_from.clone() {
+		if !seen.insert(parent.clone()) {
+			return Err(Box::new(PartialProfileError::InheritanceCycle(parent)));
+		}
+		if let Some(parent) = known.iter().find(|profile| profile.id == parent) {
+			chain.push(parent.clone());
+			continue;
+		}
+		if let Some(parent) = complete.iter().find(|profile| profile.id == parent) {
+			base = Some(parent.clone());
+			break;
+		}
+
+		let manifest = fetch_version_manifest().await?;
+		let entry =
+			manifest
+				.versions
+				.get(&parent)
+				.ok_or_else(|| PartialProfileError::MissingParent {
+					id: chain.last().unwrap().id.clone(),
+					parent,
+				})?;
+		base = Some(get_profile(entry).await?);
+		break;
+	}
+
+	let mut resolved = match base {
+		Some(base) => base,
+		None => chain.pop().unwrap().try_into()?,
+	};
+	for child in chain.into_iter().rev() {
+		resolved = child.merge(resolved);
+	}
+	Ok(resolved)
+}
+
 pub async fn get_asset_index(index: &Download) -> Result<AssetIndex> {
 	fetch_data(None, index.url.clone()).await
 }
@@ -43,3 +94,73 @@ pub async fn fetch_version_manifest() -> Result<VersionManifest> {
 pub async fn fetch_profile(version: &version_manifest::Entry) -> Result<Profile> {
 	fetch_data(None, version.url.clone()).await
 }
+
+/// Fetch the list of Java runtimes. It's always downloaded again, so new runtime versions are picked up.
+pub async fn fetch_java_runtimes() -> Result<JavaRuntimes> {
+	fetch_fresh_data(JAVA_RUNTIMES_URL.to_string()).await
+}
+
+pub async fn fetch_runtime_manifest(manifest: &Download) -> Result<RuntimeManifest> {
+	let store = fetch_sha1(manifest.sha1.clone(), manifest.url.clone()).await?;
+	Ok(serde_json::from_reader(File::open(store.path)?)?)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn partial(id: &str, inherits_from: &str) -> PartialProfile {
+		serde_json::from_value(serde_json::json!({
+			"id": id,
+			"inheritsFrom": inherits_from,
+			"libraries": [{"name": format!("example:{id}:1.0")}],
+		}))
+		.unwrap()
+	}
+
+	fn vanilla() -> Profile {
+		let path = format!(
+			"{}/tests/fixtures/profiles/1.20.1.json",
+			env!("CARGO_MANIFEST_DIR")
+		);
+		serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+	}
+
+	#[tokio::test]
+	async fn resolves_known_chains() {
+		let optifine = partial("optifine", "forge");
+		let known = [partial("forge", "1.20.1")];
+		let profile = resolve_profile(optifine, &known, &[vanilla()])
+			.await
+			.unwrap();
+
+		assert_eq!(profile.id, "optifine");
+		let vanilla_first = vanilla().libraries[0].name.clone();
+		let libraries: Vec<(&str, usize)> = profile
+			.libraries
+			.iter()
+			.take(3)
+			.map(|library| (library.name.as_str(), library.inheritance_depth))
+			.collect();
+		assert_eq!(
+			libraries,
+			[
+				("example:optifine:1.0", 0),
+				("example:forge:1.0", 1),
+				(vanilla_first.as_str(), 2),
+			]
+		);
+	}
+
+	#[tokio::test]
+	async fn rejects_cycles() {
+		let known = [partial("b", "a")];
+		let error = resolve_profile(partial("a", "b"), &known, &[])
+			.await
+			.unwrap_err();
+		assert!(matches!(
+			error.downcast_ref(),
+			Some(PartialProfileError::InheritanceCycle(id)) if id == "a"
+		));
+	}
+}
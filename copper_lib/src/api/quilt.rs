@@ -0,0 +1,15 @@
+//! Quilt's meta API, which is compatible with Fabric's (see [FabricMeta]).
+//!
+//! See <https://github.com/QuiltMC/quilt-meta>.
+
+use super::fabric::FabricMeta;
+use std::env;
+
+pub const META_URL: &str = "https://meta.quiltmc.org/v3";
+/// Environment variable that overrides [META_URL], for mirrors or a local server.
+pub const META_URL_VARIABLE: &str = "COPPER_QUILT_META_URL";
+
+/// A client for Quilt's meta API.
+pub fn meta() -> FabricMeta {
+	FabricMeta::new(env::var(META_URL_VARIABLE).unwrap_or_else(|_| META_URL.to_string()))
+}
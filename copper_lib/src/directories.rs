@@ -8,9 +8,17 @@ pub struct Paths {
 	pub cache: PathBuf,
 	pub config: PathBuf,
 
+	pub instances: PathBuf,
 	pub assets: PathBuf,
 	pub asset_indexes: PathBuf,
 	pub natives: PathBuf,
+	pub runtimes: PathBuf,
+	/// Libraries linked from the store in Maven layout, for profiles that refer to them by path (`${library_directory}`).
+	pub libraries: PathBuf,
+	/// Client jars, linked from the store as `<version>/<version>.jar`.
+	pub versions: PathBuf,
+	/// Working directories for mod loader installers, and the profiles they produce.
+	pub loaders: PathBuf,
 }
 impl Paths {
 	/// Generates all sub-directories from a single base directory name.
@@ -18,22 +26,37 @@ impl Paths {
 		let cache = dirs::cache_dir().unwrap().join(&name);
 		let config = dirs::config_dir().unwrap().join(&name);
 
+		let instances = config.join("instances");
 		let assets = cache.join("assets");
 		let asset_indexes = assets.join("indexes");
 		let natives = cache.join("natives");
+		let runtimes = cache.join("runtimes");
+		let libraries = cache.join("libraries");
+		let versions = cache.join("versions");
+		let loaders = cache.join("loaders");
 
 		// `create_dir_all` is recursive, which will result in the `cache` and `config` directories also being created.
+		create_dir_all(&instances)?;
 		create_dir_all(&asset_indexes)?;
 		create_dir_all(&natives)?;
+		create_dir_all(&runtimes)?;
+		create_dir_all(&libraries)?;
+		create_dir_all(&versions)?;
+		create_dir_all(&loaders)?;
 
 		Ok(Self {
 			name,
 			cache,
 			config,
 
+			instances,
 			assets,
 			asset_indexes,
 			natives,
+			runtimes,
+			libraries,
+			versions,
+			loaders,
 		})
 	}
 }
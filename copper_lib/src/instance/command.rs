@@ -0,0 +1,141 @@
+use std::{collections::BTreeMap, fmt::Write, path::PathBuf};
+use tokio::process::Command;
+
+/// Game arguments whose values are secret, and shouldn't be shown or logged.
+const SECRET_ARGUMENTS: &[&str] = &["--accessToken", "--session", "--xuid", "--clientId"];
+/// Environment variables with any of these in their names are assumed to be secret.
+const SECRET_ENV_WORDS: &[&str] = &["TOKEN", "SECRET", "PASSWORD", "KEY"];
+const REDACTED: &str = "<redacted>";
+
+/// Everything needed to start the game, fully resolved.
+#[derive(Clone, Debug)]
+pub struct LaunchCommand {
+	/// Wrapper commands and their arguments, which Java is run through.
+	pub wrappers: Vec<String>,
+	pub java: PathBuf,
+	pub jvm_args: Vec<String>,
+	pub main_class: String,
+	pub game_args: Vec<String>,
+	pub env: BTreeMap<String, String>,
+	pub directory: PathBuf,
+}
+
+/// Quote an argument for `sh`.
+fn shell_quote(arg: &str) -> String {
+	if !arg.is_empty()
+		&& arg
+			.chars()
+			.all(|c| c.is_ascii_alphanumeric() || "-_./=:+,@%".contains(c))
+	{
+		return arg.to_string();
+	}
+	format!("'{}'", arg.replace('\'', r"'\''"))
+}
+
+impl LaunchCommand {
+	/// The classpath passed to Java with `-cp`.
+	pub fn classpath(&self) -> Option<&str> {
+		let index = self
+			.jvm_args
+			.iter()
+			.position(|arg| arg == "-cp" || arg == "-classpath")?;
+		self.jvm_args.get(index + 1).map(String::as_str)
+	}
+
+	/// Every argument, starting with the program that's run.
+	pub fn args(&self) -> Vec<String> {
+		let mut args = self.wrappers.clone();
+		args.push(self.java.to_string_lossy().into());
+		args.extend(self.jvm_args.iter().cloned());
+		args.push(self.main_class.clone());
+		args.extend(self.game_args.iter().cloned());
+		args
+	}
+
+	/// A copy with secrets, such as the access token, replaced.
+	/// Secret game arguments are replaced wherever else their values show up, along with environment variables that look secret.
+	pub fn redacted(&self) -> Self {
+		let mut redacted = self.clone();
+		let mut secrets = Vec::new();
+		let mut secret = false;
+		for arg in &mut redacted.game_args {
+			if secret {
+				secrets.push(std::mem::replace(arg, REDACTED.into()));
+			}
+			secret = SECRET_ARGUMENTS.contains(&arg.as_str());
+		}
+		secrets.retain(|secret| !secret.is_empty() && secret != REDACTED);
+
+		let redact = |arg: &mut String| {
+			for secret in &secrets {
+				if arg.contains(secret.as_str()) {
+					*arg = arg.replace(secret.as_str(), REDACTED);
+				}
+			}
+		};
+		redacted.wrappers.iter_mut().for_each(redact);
+		redacted.jvm_args.iter_mut().for_each(redact);
+		for (key, value) in &mut redacted.env {
+			let key = key.to_uppercase();
+			if SECRET_ENV_WORDS.iter().any(|word| key.contains(word)) {
+				*value = REDACTED.into();
+			} else {
+				redact(value);
+			}
+		}
+		redacted
+	}
+
+	pub fn command(&self) -> Command {
+		let args = self.args();
+		let mut command = Command::new(&args[0]);
+		command.args(&args[1..]).envs(&self.env);
+		command
+	}
+
+	/// A shell script that runs the game without the launcher.
+	/// Hooks are run around the game like the launcher does, with `hook_env` exported for them.
+	pub fn to_shell_script(
+		&self,
+		title: &str,
+		hook_env: &BTreeMap<String, String>,
+		pre_launch: Option<&str>,
+		post_exit: Option<&str>,
+	) -> String {
+		let mut script = String::from("#!/bin/sh\n");
+		writeln!(script, "# {title}").unwrap();
+		writeln!(script, "# Extra arguments are passed on to Minecraft.\n").unwrap();
+		writeln!(
+			script,
+			"cd {} || exit 1",
+			shell_quote(&self.directory.to_string_lossy())
+		)
+		.unwrap();
+		for (key, value) in self.env.iter().chain(hook_env) {
+			writeln!(script, "export {key}={}", shell_quote(value)).unwrap();
+		}
+		script.push('\n');
+
+		if let Some(hook) = pre_launch {
+			writeln!(script, "sh -c {} || exit $?", shell_quote(hook)).unwrap();
+		}
+
+		let args: Vec<String> = self.args().iter().map(|arg| shell_quote(arg)).collect();
+		let args = args.join(" \\\n\t");
+		match post_exit {
+			Some(hook) => {
+				writeln!(script, "{args} \\\n\t\"$@\"").unwrap();
+				writeln!(script, "status=$?").unwrap();
+				writeln!(
+					script,
+					"COPPER_EXIT_CODE=$status sh -c {}",
+					shell_quote(hook)
+				)
+				.unwrap();
+				writeln!(script, "exit $status").unwrap();
+			}
+			None => writeln!(script, "exec {args} \\\n\t\"$@\"").unwrap(),
+		}
+		script
+	}
+}
@@ -0,0 +1,63 @@
+use super::{hooks::Hooks, jvm::JvmOptions, loader::Loader, logging::LogConfig};
+use crate::{Paths, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+	collections::BTreeMap,
+	fs::{self, create_dir_all},
+	path::PathBuf,
+};
+
+const CONFIG_FILE: &str = "instance.toml";
+
+/// Settings for an instance, saved as `instance.toml` in the instance's directory.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct InstanceConfig {
+	/// ID of the Minecraft version, as listed in the version manifest.
+	pub version: String,
+	/// Mod loader to install on top of the game, if any.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub loader: Option<Loader>,
+	#[serde(default)]
+	pub logging: LogConfig,
+	/// Commands the game is run through, outermost first, e.g. `["gamemoderun", "mangohud"]`.
+	/// Each one is split on whitespace, and can use the same `${...}` placeholders as version profiles.
+	#[serde(default)]
+	pub wrappers: Vec<String>,
+	#[serde(default)]
+	pub jvm: JvmOptions,
+	/// Extra environment variables for the game and hooks. Values can use `${...}` placeholders.
+	#[serde(default)]
+	pub env: BTreeMap<String, String>,
+	#[serde(default)]
+	pub hooks: Hooks,
+}
+
+impl InstanceConfig {
+	pub fn new(version: String) -> Self {
+		Self {
+			version,
+			..Default::default()
+		}
+	}
+
+	/// The instance's directory, which is also the game directory.
+	pub fn directory(path: &Paths, name: &str) -> PathBuf {
+		path.instances.join(name)
+	}
+
+	/// Load an instance's config, or `None` if there's no instance with that name.
+	pub fn load(path: &Paths, name: &str) -> Result<Option<Self>> {
+		let file = Self::directory(path, name).join(CONFIG_FILE);
+		if !file.exists() {
+			return Ok(None);
+		}
+		Ok(Some(toml::from_str(&fs::read_to_string(file)?)?))
+	}
+
+	pub fn save(&self, path: &Paths, name: &str) -> Result<()> {
+		let directory = Self::directory(path, name);
+		create_dir_all(&directory)?;
+		fs::write(directory.join(CONFIG_FILE), toml::to_string(self)?)?;
+		Ok(())
+	}
+}
@@ -0,0 +1,207 @@
+//! Finding out why the game crashed, from crash reports, JVM error logs, and the game's output.
+
+use crate::Result;
+use serde::{Deserialize, Serialize};
+use std::{
+	cmp::Reverse,
+	fmt,
+	fs::{self, read_dir},
+	path::{Path, PathBuf},
+	time::SystemTime,
+};
+
+/// Lines of context shown on either side of the line that identified the cause.
+const EXCERPT_CONTEXT: usize = 3;
+
+/// A known reason for the game crashing.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub enum CrashCause {
+	WrongJavaVersion,
+	OutOfMemory,
+	MissingModDependency,
+	MixinFailure,
+	OpenGlError,
+	Unknown,
+}
+
+/// Text that identifies each cause, checked in order, so more specific causes come first.
+const KNOWN_CAUSES: &[(CrashCause, &[&str])] = &[
+	(
+		CrashCause::WrongJavaVersion,
+		&[
+			"java.lang.UnsupportedClassVersionError",
+			"has been compiled by a more recent version of the Java Runtime",
+			"Unsupported class file major version",
+			"Unsupported major.minor version",
+		],
+	),
+	(
+		CrashCause::OutOfMemory,
+		&[
+			"java.lang.OutOfMemoryError",
+			"There is insufficient memory for the Java Runtime Environment",
+			"Native memory allocation (malloc) failed",
+		],
+	),
+	(
+		CrashCause::MissingModDependency,
+		&[
+			"Mod resolution encountered an incompatible mod set",
+			"which is missing!",
+			"Missing or unsupported mandatory dependencies",
+			"MissingModsException",
+			"net.fabricmc.loader.impl.FormattedException: Mod resolution",
+		],
+	),
+	(
+		CrashCause::MixinFailure,
+		&[
+			"org.spongepowered.asm.mixin.transformer.throwables.MixinTransformerError",
+			"org.spongepowered.asm.mixin.injection.throwables.InvalidInjectionException",
+			"MixinApplyError",
+			"Mixin apply failed",
+			"Mixin prepare failed",
+		],
+	),
+	(
+		CrashCause::OpenGlError,
+		&[
+			"GLFW error 65542",
+			"GLFW error 65543",
+			"Pixel format not accelerated",
+			"WGL: The driver does not appear to support OpenGL",
+			"Failed to create window",
+			"org.lwjgl.LWJGLException",
+			"Couldn't set pixel format",
+		],
+	),
+];
+
+impl CrashCause {
+	/// A short explanation of the cause, and what to do about it.
+	pub fn description(&self) -> &'static str {
+		match self {
+			Self::WrongJavaVersion => "The game or a mod needs a different version of Java than the one it was launched with. Check which Java version the mods support.",
+			Self::OutOfMemory => "The game ran out of memory. Increase the instance's maximum heap size, or close other programs if the system itself is out of memory.",
+			Self::MissingModDependency => "A mod is missing a dependency, or depends on a version that isn't installed. Install the mods listed below, or remove the mod that needs them.",
+			Self::MixinFailure => "A mod failed to patch the game's code. It's probably incompatible with this version of Minecraft, the mod loader, or another mod.",
+			Self::OpenGlError => "The game couldn't start OpenGL. Update your graphics drivers, and make sure the game is running on a GPU that supports OpenGL.",
+			Self::Unknown => "The cause of the crash isn't known. Check the crash report below.",
+		}
+	}
+}
+impl fmt::Display for CrashCause {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let name = match self {
+			Self::WrongJavaVersion => "Wrong Java version",
+			Self::OutOfMemory => "Out of memory",
+			Self::MissingModDependency => "Missing mod dependency",
+			Self::MixinFailure => "Mixin failed to apply",
+			Self::OpenGlError => "OpenGL error",
+			Self::Unknown => "Unknown",
+		};
+		write!(f, "{name}")
+	}
+}
+
+/// The result of analyzing a crash.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CrashAnalysis {
+	pub cause: CrashCause,
+	/// The crash report or JVM error log the cause was found in, or `None` if it was found in the game's output.
+	pub source: Option<PathBuf>,
+	/// Lines around where the cause was found, or the start of the crash report if the cause is unknown.
+	pub excerpt: Vec<String>,
+	/// Every crash report and JVM error log written during the crash.
+	pub reports: Vec<PathBuf>,
+}
+
+/// Files in a directory whose names match, and were modified after `since`.
+fn files_since(
+	directory: &Path,
+	since: SystemTime,
+	matches: impl Fn(&str) -> bool,
+) -> Vec<PathBuf> {
+	let entries = match read_dir(directory) {
+		Ok(entries) => entries,
+		Err(_) => return Vec::new(),
+	};
+	let mut files: Vec<(SystemTime, PathBuf)> = entries
+		.flatten()
+		.filter(|entry| matches(&entry.file_name().to_string_lossy()))
+		.filter_map(|entry| {
+			let modified = entry.metadata().ok()?.modified().ok()?;
+			(modified >= since).then(|| (modified, entry.path()))
+		})
+		.collect();
+	// Newest first.
+	files.sort_by_key(|(modified, _)| Reverse(*modified));
+	files.into_iter().map(|(_, path)| path).collect()
+}
+
+/// Find the first known cause in some lines, with an excerpt around where it was found.
+pub fn match_cause(lines: &[String]) -> Option<(CrashCause, Vec<String>)> {
+	for (cause, patterns) in KNOWN_CAUSES {
+		let found = lines
+			.iter()
+			.position(|line| patterns.iter().any(|pattern| line.contains(pattern)));
+		if let Some(index) = found {
+			let start = index.saturating_sub(EXCERPT_CONTEXT);
+			let end = (index + EXCERPT_CONTEXT + 1).min(lines.len());
+			return Some((*cause, lines[start..end].to_vec()));
+		}
+	}
+	None
+}
+
+/// Analyze a crash of the game running in `directory`, which was started at `since`.
+/// Crash reports are checked first, then JVM error logs (`hs_err_pid*.log`), then the game's output.
+pub fn analyze(directory: &Path, since: SystemTime, output: &[String]) -> Result<CrashAnalysis> {
+	let mut reports = files_since(&directory.join("crash-reports"), since, |name| {
+		name.starts_with("crash-") && name.ends_with(".txt")
+	});
+	reports.extend(files_since(directory, since, |name| {
+		name.starts_with("hs_err_pid") && name.ends_with(".log")
+	}));
+
+	let mut first_report = None;
+	for report in &reports {
+		let lines: Vec<String> = fs::read_to_string(report)?
+			.lines()
+			.map(String::from)
+			.collect();
+		if let Some((cause, excerpt)) = match_cause(&lines) {
+			return Ok(CrashAnalysis {
+				cause,
+				source: Some(report.clone()),
+				excerpt,
+				reports,
+			});
+		}
+		first_report.get_or_insert((report.clone(), lines));
+	}
+
+	if let Some((cause, excerpt)) = match_cause(output) {
+		return Ok(CrashAnalysis {
+			cause,
+			source: None,
+			excerpt,
+			reports,
+		});
+	}
+
+	// Crash reports start with a comment, the time, and a description, followed by the stack trace.
+	let (source, excerpt) = match first_report {
+		Some((report, lines)) => (Some(report), lines.into_iter().take(12).collect()),
+		None => {
+			let start = output.len().saturating_sub(12);
+			(None, output[start..].to_vec())
+		}
+	};
+	Ok(CrashAnalysis {
+		cause: CrashCause::Unknown,
+		source,
+		excerpt,
+		reports,
+	})
+}
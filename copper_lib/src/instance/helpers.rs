@@ -1,27 +1,248 @@
-use crate::{store::fetch, structs::Profile, Result};
+use super::libraries::{self, LibraryError};
+use crate::{
+	api::maven::{MavenRepository, MOJANG_LIBRARIES},
+	store::{fetch, get, hard_link, StoreInput, StoreOutput},
+	structs::{
+		profile::{Download, Library},
+		Profile,
+	},
+	Paths, Result,
+};
+use std::{collections::HashSet, future::Future, path::PathBuf, pin::Pin};
+use tokio::task::JoinHandle;
+use tracing::info;
 
-pub async fn generate_classpath(profile: &Profile) -> Result<String> {
-	let mut classpath = Vec::new();
+type StoreFuture = Pin<Box<dyn Future<Output = Result<StoreOutput>> + Send>>;
+
+/// A fetch, keyed by what it fetches (a URL or sha512), along with something to keep with its result.
+type Fetch<T> = (String, StoreFuture, T);
 
-	for library in &profile.libraries {
-		if !library.is_active() {
-			continue;
+/// Run fetches concurrently. Results are in the same order as the fetches, and each key is only fetched once.
+async fn fetch_all<T>(
+	fetches: impl IntoIterator<Item = Fetch<T>>,
+) -> Result<Vec<(StoreOutput, T)>> {
+	let mut seen = HashSet::new();
+	let handles: Vec<(JoinHandle<Result<StoreOutput>>, T)> = fetches
+		.into_iter()
+		.filter(|(key, _, _)| seen.insert(key.clone()))
+		.map(|(_, fetch, data)| (tokio::spawn(fetch), data))
+		.collect();
+
+	let mut outputs = Vec::with_capacity(handles.len());
+	for (handle, data) in handles {
+		outputs.push((handle.await??, data));
+	}
+	Ok(outputs)
+}
+
+/// Fetch a download, by its sha512 if copper made it locally, or by URL otherwise.
+fn fetch_download<T>(download: &Download, data: T) -> Fetch<T> {
+	let key = download.sha512.clone().unwrap_or(download.url.clone());
+	(
+		key,
+		Box::pin(fetch(download.sha512.clone(), download.url.clone())),
+		data,
+	)
+}
+
+/// Where a library goes in the libraries directory, in Maven layout.
+pub fn library_path(library: &Library) -> Option<String> {
+	match library
+		.downloads
+		.artifact
+		.as_ref()
+		.and_then(|artifact| artifact.path.clone())
+	{
+		Some(path) => Some(path),
+		None => library
+			.coordinate()
+			.ok()
+			.map(|coordinate| coordinate.path()),
+	}
+}
+
+/// Fetch a library's jar, from its `downloads` if it has them, or from its Maven repository otherwise.
+/// Libraries that only have natives don't have a jar.
+fn fetch_library<T>(library: &Library, data: T) -> Result<Option<Fetch<T>>> {
+	if let Some(artifact) = &library.downloads.artifact {
+		if artifact.url.is_empty() && artifact.sha512.is_none() {
+			return Err(Box::new(LibraryError::Unavailable(library.name.clone())));
 		}
+		return Ok(Some(fetch_download(artifact, data)));
+	}
+	if library.downloads.classifiers.is_some() {
+		return Ok(None);
+	}
 
-		if let Some(artifact) = &library.downloads.artifact {
-			classpath.push(format!(
-				"{}",
-				fetch(None, artifact.url.clone()).await?.path.display()
-			));
+	let coordinate = library.coordinate()?;
+	let repository = MavenRepository::new(library.url.as_deref().unwrap_or(MOJANG_LIBRARIES));
+	let url = repository.url(&coordinate);
+	Ok(Some((
+		url,
+		Box::pin(async move { repository.fetch(&coordinate).await }),
+		data,
+	)))
+}
+
+/// The native classifier of a library for the current OS, if it has one.
+pub fn native_download(library: &Library) -> Option<&Download> {
+	let classifiers = library.downloads.classifiers.as_ref()?;
+	match os_info::get().os_type() {
+		os_info::Type::Windows => classifiers.natives_windows.as_ref(),
+		os_info::Type::Macos => classifiers.natives_macos.as_ref(),
+		_ => classifiers.natives_linux.as_ref(),
+	}
+}
+
+/// Fetch libraries into the store concurrently, along with where each one goes in Maven layout (see [library_path]).
+pub async fn fetch_libraries(libraries: &[Library]) -> Result<Vec<(StoreOutput, Option<String>)>> {
+	let mut fetches = Vec::new();
+	for library in libraries {
+		fetches.extend(fetch_library(library, library_path(library))?);
+	}
+	fetch_all(fetches).await
+}
+
+/// Fetch libraries concurrently, and hardlink them into the libraries directory.
+/// Returns their paths in the same order, without duplicates.
+pub async fn link_libraries(path: &Paths, libraries: &[Library]) -> Result<Vec<PathBuf>> {
+	let mut seen = HashSet::new();
+	let mut linked = Vec::new();
+	for (output, library_path) in fetch_libraries(libraries).await? {
+		// Libraries that can't be placed in Maven layout are used straight from the store.
+		let destination = match library_path {
+			Some(library_path) => {
+				let destination = path.libraries.join(library_path);
+				hard_link(&output, &destination, false)?;
+				destination
+			}
+			None => output.path,
+		};
+		if seen.insert(destination.clone()) {
+			linked.push(destination);
 		}
 	}
-	classpath.push(format!(
-		"{}",
-		fetch(None, profile.downloads.client.url.clone())
-			.await?
-			.path
-			.display()
-	));
+	Ok(linked)
+}
+
+/// Where the client jar is linked to, as `<version>/<version>.jar`.
+pub fn client_path(path: &Paths, profile: &Profile) -> PathBuf {
+	path.versions
+		.join(&profile.id)
+		.join(format!("{}.jar", profile.id))
+}
+
+/// Link the client jar as `<version>/<version>.jar`, since some mod loaders find it by name.
+pub async fn link_client(path: &Paths, profile: &Profile) -> Result<PathBuf> {
+	let client = &profile.downloads.client;
+	let output = fetch(client.sha512.clone(), client.url.clone()).await?;
+	let destination = client_path(path, profile);
+	hard_link(&output, &destination, false)?;
+	Ok(destination)
+}
+
+/// Resolve every active library (and the client jar last) through the store, and link them into place.
+/// Libraries are fetched concurrently, but the classpath is always in profile order, with one version of each library (see [libraries::resolve]).
+pub async fn generate_classpath(path: &Paths, profile: &Profile) -> Result<Vec<PathBuf>> {
+	let (libraries, overrides) = libraries::resolve(&profile.libraries);
+	for library_override in overrides {
+		info!("{library_override}");
+	}
+
+	let (mut classpath, client) =
+		tokio::try_join!(link_libraries(path, &libraries), link_client(path, profile))?;
+	if !classpath.contains(&client) {
+		classpath.push(client);
+	}
+	Ok(classpath)
+}
+
+/// Where a library's jar is on the classpath, without fetching or linking it. `None` for libraries that only have natives.
+/// Libraries that can't be placed in Maven layout are looked up in the store, so they have to be there already.
+fn planned_library(path: &Paths, library: &Library) -> Result<Option<PathBuf>> {
+	let artifact = library.downloads.artifact.as_ref();
+	if artifact.is_none() && library.downloads.classifiers.is_some() {
+		return Ok(None);
+	}
+	let destination = match (library_path(library), artifact) {
+		(Some(library_path), _) => path.libraries.join(library_path),
+		(None, Some(artifact)) => {
+			let input = match &artifact.sha512 {
+				Some(sha512) => StoreInput::Sha512(sha512.clone()),
+				None => StoreInput::URL(artifact.url.clone()),
+			};
+			get(input)?.path
+		}
+		(None, None) => return Err(Box::new(LibraryError::Unavailable(library.name.clone()))),
+	};
+	Ok(Some(destination))
+}
+
+/// The classpath [generate_classpath] makes, without fetching or linking anything.
+pub fn planned_classpath(path: &Paths, profile: &Profile) -> Result<Vec<PathBuf>> {
+	let (libraries, _) = libraries::resolve(&profile.libraries);
+	let mut classpath = Vec::new();
+	for library in &libraries {
+		if let Some(destination) = planned_library(path, library)? {
+			if !classpath.contains(&destination) {
+				classpath.push(destination);
+			}
+		}
+	}
+
+	let client = client_path(path, profile);
+	if !classpath.contains(&client) {
+		classpath.push(client);
+	}
+	Ok(classpath)
+}
+
+/// Link just the parts of the classpath that are in `drifted` again, such as files that were deleted or replaced.
+pub async fn relink_classpath(
+	path: &Paths,
+	profile: &Profile,
+	drifted: &HashSet<PathBuf>,
+) -> Result<()> {
+	let (libraries, _) = libraries::resolve(&profile.libraries);
+	let mut relink = Vec::new();
+	for library in libraries {
+		if planned_library(path, &library)?
+			.is_some_and(|destination| drifted.contains(&destination))
+		{
+			relink.push(library);
+		}
+	}
+	link_libraries(path, &relink).await?;
+	if drifted.contains(&client_path(path, profile)) {
+		link_client(path, profile).await?;
+	}
+	Ok(())
+}
+
+/// Join a classpath into the `-cp` argument.
+pub fn join_classpath(classpath: &[PathBuf]) -> String {
+	let classpath: Vec<String> = classpath
+		.iter()
+		.map(|path| path.display().to_string())
+		.collect();
+	classpath.join(":")
+}
+
+/// The native jars for the current OS, from the same libraries as the classpath (see [libraries::resolve]).
+pub fn native_downloads(profile: &Profile) -> Vec<Download> {
+	let (libraries, _) = libraries::resolve(&profile.libraries);
+	libraries
+		.iter()
+		.filter_map(native_download)
+		.cloned()
+		.collect()
+}
 
-	Ok(classpath.join(":"))
+/// Fetch the native jars for the current OS into the store, so they're ready to be extracted.
+pub async fn fetch_natives(profile: &Profile) -> Result<()> {
+	let fetches = native_downloads(profile)
+		.into_iter()
+		.map(|natives| fetch_download(&natives, ()));
+	fetch_all(fetches).await?;
+	Ok(())
 }
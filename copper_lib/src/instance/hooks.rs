@@ -0,0 +1,53 @@
+use crate::Result;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, error::Error, fmt, path::Path};
+use tokio::process::Command;
+use tracing::info;
+
+/// Shell commands run around the game. They're run with `sh -c` in the instance's directory,
+/// with information about the instance in `COPPER_*` environment variables.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct Hooks {
+	/// Run before the game starts. Exiting with a non-zero code stops the game from launching.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub pre_launch: Option<String>,
+	/// Run after the game exits, with its exit code in `COPPER_EXIT_CODE`.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub post_exit: Option<String>,
+}
+
+#[derive(Debug)]
+pub enum HookError {
+	Failed { hook: String, code: Option<i32> },
+}
+impl fmt::Display for HookError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Failed {
+				hook,
+				code: Some(code),
+			} => write!(f, "Hook `{hook}` exited with code {code}"),
+			Self::Failed { hook, code: None } => write!(f, "Hook `{hook}` was killed"),
+		}
+	}
+}
+impl Error for HookError {}
+
+/// Run a hook, failing if it exits with a non-zero code.
+pub async fn run(hook: &str, directory: &Path, env: &BTreeMap<String, String>) -> Result<()> {
+	info!("Running hook `{hook}`...");
+	let status = Command::new("sh")
+		.arg("-c")
+		.arg(hook)
+		.current_dir(directory)
+		.envs(env)
+		.status()
+		.await?;
+	if !status.success() {
+		return Err(Box::new(HookError::Failed {
+			hook: hook.to_string(),
+			code: status.code(),
+		}));
+	}
+	Ok(())
+}
@@ -0,0 +1,483 @@
+//! Running Forge and NeoForge installers.
+//!
+//! Their installers contain an `install_profile.json`, listing "processors": Java programs that make the patched client jar
+//! and other libraries the game needs. Copper runs them like the official installer does, then saves everything they made
+//! to the store, so the resulting profile can be used again without running anything.
+
+use super::helpers::{fetch_libraries, library_path};
+use crate::{
+	api::maven::MavenRepository,
+	java,
+	store::{fetch_sha1, get, hard_link, import, StoreInput},
+	structs::{
+		install_profile::{InstallProfile, Processor},
+		profile::Library,
+		MavenCoordinate, PartialProfile, Profile,
+	},
+	Paths, Result,
+};
+use data_encoding::HEXLOWER;
+use sha1::{Digest, Sha1};
+use std::{
+	collections::{BTreeMap, BTreeSet, HashMap},
+	error::Error,
+	fmt,
+	fs::{self, File},
+	io::{self, Read},
+	path::{Path, PathBuf},
+};
+use tokio::process::Command;
+use tracing::info;
+use zip::ZipArchive;
+
+/// How many lines of a failed processor's output are included in the error.
+const FAILURE_OUTPUT_LINES: usize = 20;
+
+/// Files the installer made that the game finds in the libraries directory rather than on the classpath (like Forge's
+/// `client-<version>-srg.jar`), by their path in Maven layout, and their sha512.
+type Outputs = BTreeMap<String, String>;
+
+/// Where an install's [Outputs] are saved, next to its cached profile.
+fn outputs_file(cache_file: &Path) -> PathBuf {
+	cache_file.with_extension("outputs.json")
+}
+
+#[derive(Debug)]
+pub enum InstallerError {
+	Unsupported(String),
+	NoMainClass(String),
+	ProcessorFailed {
+		jar: String,
+		code: Option<i32>,
+		output: String,
+	},
+	OutputMismatch(PathBuf),
+	MissingOutput(String),
+}
+impl fmt::Display for InstallerError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Unsupported(installer) => write!(
+				f,
+				"{installer} is an old-style installer, only installers for Minecraft 1.13 and newer are supported"
+			),
+			Self::NoMainClass(jar) => write!(f, "Processor {jar} has no Main-Class"),
+			Self::ProcessorFailed { jar, code, output } => {
+				match code {
+					Some(code) => write!(f, "Processor {jar} exited with code {code}")?,
+					None => write!(f, "Processor {jar} was killed")?,
+				}
+				write!(f, ":\n{output}")
+			}
+			Self::OutputMismatch(path) => {
+				write!(f, "{} doesn't match its expected sha1", path.display())
+			}
+			Self::MissingOutput(library) => {
+				write!(f, "The installer didn't make library {library}")
+			}
+		}
+	}
+}
+impl Error for InstallerError {}
+
+fn sha1_file(path: &Path) -> Result<String> {
+	let mut hasher = Sha1::new();
+	io::copy(&mut File::open(path)?, &mut hasher)?;
+	Ok(HEXLOWER.encode(&hasher.finalize()))
+}
+
+fn read_entry(zip: &mut ZipArchive<File>, name: &str) -> Result<Vec<u8>> {
+	let mut data = Vec::new();
+	zip.by_name(name.trim_start_matches('/'))?
+		.read_to_end(&mut data)?;
+	Ok(data)
+}
+
+/// Main class of a jar, from its manifest.
+fn main_class(jar: &Path) -> Result<Option<String>> {
+	let mut zip = ZipArchive::new(File::open(jar)?)?;
+	let manifest = String::from_utf8(read_entry(&mut zip, "META-INF/MANIFEST.MF")?)?;
+	Ok(manifest.lines().find_map(|line| {
+		line.strip_prefix("Main-Class:")
+			.map(|class| class.trim().to_string())
+	}))
+}
+
+/// Path of a library in the installer's libraries directory, from a `[group:artifact:version]` reference.
+fn library_reference(value: &str, libraries: &Path) -> Result<Option<PathBuf>> {
+	match value
+		.strip_prefix('[')
+		.and_then(|value| value.strip_suffix(']'))
+	{
+		Some(coordinate) => Ok(Some(
+			libraries.join(coordinate.parse::<MavenCoordinate>()?.path()),
+		)),
+		None => Ok(None),
+	}
+}
+
+/// Substitute library references and `{KEY}` data values into a processor argument or output.
+fn resolve_argument(arg: &str, data: &HashMap<String, String>, libraries: &Path) -> Result<String> {
+	if let Some(path) = library_reference(arg, libraries)? {
+		return Ok(path.to_string_lossy().into());
+	}
+	let mut arg = arg.to_string();
+	for (key, value) in data {
+		arg = arg.replace(&format!("{{{key}}}"), value);
+	}
+	Ok(arg)
+}
+
+/// A cached install is only usable if everything it made is still in the store.
+/// Its outputs are linked back into the libraries directory, in case they've been removed from there.
+fn load_cached(path: &Paths, file: &Path) -> Option<PartialProfile> {
+	let profile: PartialProfile = serde_json::from_str(&fs::read_to_string(file).ok()?).ok()?;
+	let complete = profile.libraries.iter().all(|library| {
+		match library
+			.downloads
+			.artifact
+			.as_ref()
+			.and_then(|artifact| artifact.sha512.clone())
+		{
+			Some(sha512) => get(StoreInput::Sha512(sha512)).is_ok(),
+			None => true,
+		}
+	});
+	if !complete {
+		return None;
+	}
+
+	let outputs: Outputs =
+		serde_json::from_str(&fs::read_to_string(outputs_file(file)).ok()?).ok()?;
+	for (library_path, sha512) in outputs {
+		let output = get(StoreInput::Sha512(sha512)).ok()?;
+		hard_link(&output, &path.libraries.join(library_path), false).ok()?;
+	}
+	Some(profile)
+}
+
+/// Run an installer from a Maven repository, returning the version profile it makes.
+/// The profile is cached, so installing the same version again is instant.
+pub async fn install(
+	path: &Paths,
+	vanilla: &Profile,
+	repository: &MavenRepository,
+	installer: &MavenCoordinate,
+) -> Result<PartialProfile> {
+	let cache_file = path
+		.loaders
+		.join(format!("{}-{}.json", installer.artifact, installer.version));
+	if let Some(profile) = load_cached(path, &cache_file) {
+		return Ok(profile);
+	}
+
+	info!("Running the {installer} installer...");
+	let installer_jar = repository.fetch(installer).await?.path;
+	let mut zip = ZipArchive::new(File::open(&installer_jar)?)?;
+	let install_profile: InstallProfile =
+		match serde_json::from_slice(&read_entry(&mut zip, "install_profile.json")?) {
+			Ok(install_profile) => install_profile,
+			Err(_) => return Err(Box::new(InstallerError::Unsupported(installer.to_string()))),
+		};
+	let mut profile: PartialProfile =
+		serde_json::from_slice(&read_entry(&mut zip, &install_profile.json)?)?;
+
+	let work = path.loaders.join("work").join(&install_profile.version);
+	if work.exists() {
+		fs::remove_dir_all(&work)?;
+	}
+	let libraries = work.join("libraries");
+	fs::create_dir_all(&libraries)?;
+
+	// Libraries bundled in the installer's `maven` directory go straight into the store.
+	let mut bundled = HashMap::new();
+	for i in 0..zip.len() {
+		let mut file = zip.by_index(i)?;
+		let name = match file.name().strip_prefix("maven/") {
+			Some(name) if file.is_file() => name.to_string(),
+			_ => continue,
+		};
+		let mut data = Vec::new();
+		file.read_to_end(&mut data)?;
+		bundled.insert(name, import(data)?);
+	}
+
+	// Everything else is downloaded. Files are copied rather than hardlinked, since processors can overwrite their outputs.
+	let (downloadable, local): (Vec<Library>, Vec<Library>) = install_profile
+		.libraries
+		.iter()
+		.chain(&profile.libraries)
+		.cloned()
+		.partition(|library| {
+			library
+				.downloads
+				.artifact
+				.as_ref()
+				.is_none_or(|artifact| !artifact.url.is_empty())
+		});
+	for (output, library_path) in fetch_libraries(&downloadable).await? {
+		if let Some(library_path) = library_path {
+			let destination = libraries.join(library_path);
+			fs::create_dir_all(destination.parent().unwrap())?;
+			fs::copy(&output.path, destination)?;
+		}
+	}
+	for library in &local {
+		if let Some(output) = library_path(library).and_then(|path| bundled.get(&path)) {
+			let destination = libraries.join(library_path(library).unwrap());
+			fs::create_dir_all(destination.parent().unwrap())?;
+			fs::copy(&output.path, destination)?;
+		}
+	}
+
+	let minecraft_jar = work
+		.join("versions")
+		.join(&vanilla.id)
+		.join(format!("{}.jar", vanilla.id));
+	fs::create_dir_all(minecraft_jar.parent().unwrap())?;
+	// Processors patch this jar, so a corrupted download would end up in every file they make.
+	let client = &vanilla.downloads.client;
+	fs::copy(
+		fetch_sha1(client.sha1.clone(), client.url.clone())
+			.await?
+			.path,
+		&minecraft_jar,
+	)?;
+
+	let mut data = HashMap::from([
+		("SIDE".to_string(), "client".to_string()),
+		(
+			"MINECRAFT_JAR".into(),
+			minecraft_jar.to_string_lossy().into(),
+		),
+		(
+			"MINECRAFT_VERSION".into(),
+			install_profile.minecraft.clone(),
+		),
+		("ROOT".into(), work.to_string_lossy().into()),
+		("INSTALLER".into(), installer_jar.to_string_lossy().into()),
+		("LIBRARY_DIR".into(), libraries.to_string_lossy().into()),
+	]);
+	for (key, entry) in &install_profile.data {
+		let value = &entry.client;
+		let value = if let Some(path) = library_reference(value, &libraries)? {
+			path.to_string_lossy().into()
+		} else if let Some(literal) = value
+			.strip_prefix('\'')
+			.and_then(|value| value.strip_suffix('\''))
+		{
+			literal.to_string()
+		} else if value.starts_with('/') {
+			// Files within the installer, like Forge's binary patches.
+			let destination = work.join("data").join(value.trim_start_matches('/'));
+			fs::create_dir_all(destination.parent().unwrap())?;
+			fs::write(&destination, read_entry(&mut zip, value)?)?;
+			destination.to_string_lossy().into()
+		} else {
+			value.clone()
+		};
+		data.insert(key.clone(), value);
+	}
+
+	let java = java::resolve(path, &vanilla.java_version).await?;
+	let processors: Vec<&Processor> = install_profile
+		.processors
+		.iter()
+		.filter(|processor| processor.runs_on_client())
+		.collect();
+	for processor in &processors {
+		run_processor(processor, &java.path, &data, &libraries, &work).await?;
+	}
+
+	// Libraries the data refers to (like the deobfuscated client jar) and everything the processors made are found by path
+	// when the game starts, so they're kept in the store and linked into the libraries directory.
+	let mut made = BTreeSet::new();
+	for entry in install_profile.data.values() {
+		made.extend(library_reference(&entry.client, &libraries)?);
+	}
+	for processor in &processors {
+		for file in processor.outputs.keys() {
+			made.insert(PathBuf::from(resolve_argument(file, &data, &libraries)?));
+		}
+	}
+	let mut outputs = Outputs::new();
+	for file in made {
+		let library_path = match file.strip_prefix(&libraries) {
+			Ok(library_path) if file.exists() => library_path.to_string_lossy().to_string(),
+			_ => continue,
+		};
+		let output = import(fs::read(&file)?)?;
+		hard_link(&output, &path.libraries.join(&library_path), false)?;
+		outputs.insert(library_path, output.sha512);
+	}
+
+	// Save everything the installer made to the store, so the profile can find it without the installer.
+	for library in &mut profile.libraries {
+		let library_path = library_path(library);
+		let artifact = match &mut library.downloads.artifact {
+			Some(artifact) if artifact.url.is_empty() => artifact,
+			_ => continue,
+		};
+		let file = match library_path {
+			Some(library_path) => libraries.join(library_path),
+			None => continue,
+		};
+		if !file.exists() {
+			return Err(Box::new(InstallerError::MissingOutput(
+				library.name.clone(),
+			)));
+		}
+		artifact.sha512 = Some(import(fs::read(file)?)?.sha512);
+	}
+
+	fs::write(outputs_file(&cache_file), serde_json::to_string(&outputs)?)?;
+	fs::write(&cache_file, serde_json::to_string(&profile)?)?;
+	fs::remove_dir_all(&work)?;
+	Ok(profile)
+}
+
+async fn run_processor(
+	processor: &Processor,
+	java: &Path,
+	data: &HashMap<String, String>,
+	libraries: &Path,
+	work: &Path,
+) -> Result<()> {
+	let mut outputs = Vec::new();
+	for (file, sha1) in &processor.outputs {
+		let file = PathBuf::from(resolve_argument(file, data, libraries)?);
+		let sha1 = resolve_argument(sha1, data, libraries)?;
+		outputs.push((file, sha1.trim_matches('\'').to_string()));
+	}
+	// Like the official installer, processors whose outputs are already there aren't run again.
+	let done = !outputs.is_empty()
+		&& outputs
+			.iter()
+			.all(|(file, sha1)| sha1_file(file).is_ok_and(|hash| &hash == sha1));
+	if done {
+		return Ok(());
+	}
+
+	let jar = libraries.join(processor.jar.parse::<MavenCoordinate>()?.path());
+	let main_class =
+		main_class(&jar)?.ok_or_else(|| InstallerError::NoMainClass(processor.jar.clone()))?;
+	let mut classpath = vec![jar.to_string_lossy().to_string()];
+	for library in &processor.classpath {
+		classpath.push(
+			libraries
+				.join(library.parse::<MavenCoordinate>()?.path())
+				.to_string_lossy()
+				.into(),
+		);
+	}
+	let mut args = Vec::new();
+	for arg in &processor.args {
+		args.push(resolve_argument(arg, data, libraries)?);
+	}
+
+	info!("Running processor {}...", processor.jar);
+	let output = Command::new(java)
+		.arg("-cp")
+		.arg(classpath.join(":"))
+		.arg(main_class)
+		.args(args)
+		.current_dir(work)
+		.output()
+		.await?;
+	if !output.status.success() {
+		let text =
+			String::from_utf8_lossy(&output.stdout) + String::from_utf8_lossy(&output.stderr);
+		let lines: Vec<&str> = text.lines().collect();
+		let tail = lines[lines.len().saturating_sub(FAILURE_OUTPUT_LINES)..].join("\n");
+		return Err(Box::new(InstallerError::ProcessorFailed {
+			jar: processor.jar.clone(),
+			code: output.status.code(),
+			output: tail,
+		}));
+	}
+
+	for (file, sha1) in outputs {
+		if sha1_file(&file)? != sha1 {
+			return Err(Box::new(InstallerError::OutputMismatch(file)));
+		}
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn finds_library_references() {
+		let libraries = Path::new("/work/libraries");
+		assert_eq!(
+			library_reference(
+				"[net.minecraft:client:1.20.1-20230612.114412:srg]",
+				libraries
+			)
+			.unwrap(),
+			Some(libraries.join(
+				"net/minecraft/client/1.20.1-20230612.114412/client-1.20.1-20230612.114412-srg.jar"
+			))
+		);
+		assert_eq!(
+			library_reference(
+				"[de.oceanlabs.mcp:mcp_config:1.20.1-20230612.114412:mappings@txt]",
+				libraries
+			)
+			.unwrap(),
+			Some(libraries.join("de/oceanlabs/mcp/mcp_config/1.20.1-20230612.114412/mcp_config-1.20.1-20230612.114412-mappings.txt"))
+		);
+		// Anything that isn't in brackets is left to the caller.
+		for value in [
+			"'abc123'",
+			"/data/client.lzma",
+			"{MINECRAFT_JAR}",
+			"[unclosed",
+		] {
+			assert_eq!(library_reference(value, libraries).unwrap(), None);
+		}
+		assert!(library_reference("[not a coordinate]", libraries).is_err());
+	}
+
+	#[test]
+	fn resolves_arguments() {
+		let libraries = Path::new("/work/libraries");
+		let data = HashMap::from([
+			("SIDE".to_string(), "client".to_string()),
+			(
+				"MINECRAFT_JAR".into(),
+				"/work/versions/1.20.1/1.20.1.jar".into(),
+			),
+			("MAPPINGS".into(), "/work/libraries/mappings.txt".into()),
+		]);
+		assert_eq!(
+			resolve_argument("{MINECRAFT_JAR}", &data, libraries).unwrap(),
+			"/work/versions/1.20.1/1.20.1.jar"
+		);
+		// Values can be part of a larger argument, and there can be several of them.
+		assert_eq!(
+			resolve_argument("--{SIDE}={MAPPINGS}", &data, libraries).unwrap(),
+			"--client=/work/libraries/mappings.txt"
+		);
+		assert_eq!(
+			resolve_argument(
+				"[net.minecraftforge:forge:1.20.1-47.1.0:client]",
+				&data,
+				libraries
+			)
+			.unwrap(),
+			"/work/libraries/net/minecraftforge/forge/1.20.1-47.1.0/forge-1.20.1-47.1.0-client.jar"
+		);
+		// Unknown keys and plain text are passed through unchanged.
+		assert_eq!(
+			resolve_argument("{UNKNOWN}", &data, libraries).unwrap(),
+			"{UNKNOWN}"
+		);
+		assert_eq!(
+			resolve_argument("--task", &data, libraries).unwrap(),
+			"--task"
+		);
+	}
+}
@@ -0,0 +1,330 @@
+use crate::Result;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashSet, error::Error, fmt, fs, process::Command, str::FromStr};
+use tracing::warn;
+
+/// Fraction of physical memory used for the maximum heap when it's sized automatically.
+const AUTO_MEMORY_FRACTION: u64 = 4;
+const AUTO_MEMORY_MIN: u64 = 1024;
+const AUTO_MEMORY_MAX: u64 = 8192;
+
+/// Flags that newer versions of Java refuse to start with, and the major version they stopped working in.
+const REMOVED_FLAGS: &[(&str, i32)] = &[
+	("-XX:UseParNewGC", 10),
+	("-XX:AggressiveOpts", 12),
+	("-XX:UseConcMarkSweepGC", 14),
+	("-XX:PermSize", 17),
+	("-XX:MaxPermSize", 17),
+];
+
+/// Options passed to Java, on top of the ones from the version profile. Memory is in MiB.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct JvmOptions {
+	/// Initial heap size (`-Xms`).
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub min_memory: Option<u64>,
+	/// Maximum heap size (`-Xmx`).
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub max_memory: Option<u64>,
+	/// Size the maximum heap from physical memory, if `max_memory` isn't set.
+	#[serde(default)]
+	pub auto_memory: bool,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub gc: Option<GcPreset>,
+	#[serde(default)]
+	pub extra_args: Vec<String>,
+}
+
+/// A garbage collector, along with flags tuned for Minecraft.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum GcPreset {
+	/// G1 with Aikar's flags.
+	G1,
+	Zgc,
+	Shenandoah,
+}
+impl GcPreset {
+	/// The oldest major version of Java that supports this collector without experimental flags.
+	pub fn min_java_version(&self) -> i32 {
+		match self {
+			Self::G1 => 8,
+			Self::Zgc => 15,
+			Self::Shenandoah => 12,
+		}
+	}
+
+	/// The collector a flag like `-XX:+UseZGC` selects.
+	pub fn from_flag(flag: &str) -> Option<Self> {
+		match flag {
+			"-XX:+UseG1GC" => Some(Self::G1),
+			"-XX:+UseZGC" => Some(Self::Zgc),
+			"-XX:+UseShenandoahGC" => Some(Self::Shenandoah),
+			_ => None,
+		}
+	}
+
+	pub fn flags(&self, java_version: i32) -> Vec<String> {
+		let flags: &[&str] = match self {
+			Self::G1 => &[
+				"-XX:+UseG1GC",
+				"-XX:+ParallelRefProcEnabled",
+				"-XX:MaxGCPauseMillis=200",
+				"-XX:+UnlockExperimentalVMOptions",
+				"-XX:+DisableExplicitGC",
+				"-XX:+AlwaysPreTouch",
+				"-XX:G1NewSizePercent=30",
+				"-XX:G1MaxNewSizePercent=40",
+				"-XX:G1HeapRegionSize=8M",
+				"-XX:G1ReservePercent=20",
+				"-XX:G1HeapWastePercent=5",
+				"-XX:G1MixedGCCountTarget=4",
+				"-XX:InitiatingHeapOccupancyPercent=15",
+				"-XX:G1MixedGCLiveThresholdPercent=90",
+				"-XX:G1RSetUpdatingPauseTimePercent=5",
+				"-XX:SurvivorRatio=32",
+				"-XX:+PerfDisableSharedMem",
+				"-XX:MaxTenuringThreshold=1",
+			],
+			// Generational ZGC is opt-in on Java 21 and 22, and the default afterwards.
+			Self::Zgc if (21..23).contains(&java_version) => &["-XX:+UseZGC", "-XX:+ZGenerational"],
+			Self::Zgc => &["-XX:+UseZGC"],
+			Self::Shenandoah => &["-XX:+UseShenandoahGC"],
+		};
+		flags.iter().map(|flag| flag.to_string()).collect()
+	}
+}
+impl fmt::Display for GcPreset {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::G1 => write!(f, "g1"),
+			Self::Zgc => write!(f, "zgc"),
+			Self::Shenandoah => write!(f, "shenandoah"),
+		}
+	}
+}
+impl FromStr for GcPreset {
+	type Err = JvmOptionsError;
+
+	fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+		match s.to_lowercase().as_str() {
+			"g1" => Ok(Self::G1),
+			"zgc" => Ok(Self::Zgc),
+			"shenandoah" => Ok(Self::Shenandoah),
+			_ => Err(JvmOptionsError::UnknownGc(s.to_string())),
+		}
+	}
+}
+
+#[derive(Debug)]
+pub enum JvmOptionsError {
+	HeapTooLarge {
+		requested: u64,
+		physical: u64,
+	},
+	MinAboveMax {
+		min: u64,
+		max: u64,
+	},
+	UnsupportedGc {
+		gc: GcPreset,
+		java_version: i32,
+	},
+	UnknownGc(String),
+	InvalidMemory(String),
+	RemovedFlag {
+		flag: String,
+		removed_in: i32,
+		java_version: i32,
+	},
+}
+impl fmt::Display for JvmOptionsError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::HeapTooLarge {
+				requested,
+				physical,
+			} => write!(
+				f,
+				"Heap size of {requested} MiB is larger than the {physical} MiB of physical memory"
+			),
+			Self::MinAboveMax { min, max } => write!(
+				f,
+				"Minimum heap size ({min} MiB) is larger than the maximum ({max} MiB)"
+			),
+			Self::UnsupportedGc { gc, java_version } => write!(
+				f,
+				"The {gc} garbage collector needs Java {} or newer, but this instance uses Java {java_version}",
+				gc.min_java_version()
+			),
+			Self::UnknownGc(gc) => write!(
+				f,
+				"Unknown garbage collector `{gc}`, expected g1, zgc or shenandoah"
+			),
+			Self::InvalidMemory(memory) => write!(
+				f,
+				"Invalid memory size `{memory}`, expected e.g. `4096`, `512M` or `4G`"
+			),
+			Self::RemovedFlag {
+				flag,
+				removed_in,
+				java_version,
+			} => write!(
+				f,
+				"`{flag}` was removed in Java {removed_in}, but this instance uses Java {java_version}"
+			),
+		}
+	}
+}
+impl Error for JvmOptionsError {}
+
+/// Parse a memory size in MiB. Accepts a plain number of MiB, or a number with a `K`, `M`, or `G` suffix like Java does.
+pub fn parse_memory(memory: &str) -> std::result::Result<u64, JvmOptionsError> {
+	let invalid = || JvmOptionsError::InvalidMemory(memory.to_string());
+	let (number, multiplier) = match memory.chars().last().map(|c| c.to_ascii_uppercase()) {
+		Some('G') => (&memory[..memory.len() - 1], 1024),
+		Some('M') => (&memory[..memory.len() - 1], 1),
+		Some('K') => {
+			let kib: u64 = memory[..memory.len() - 1].parse().map_err(|_| invalid())?;
+			return Ok(kib / 1024);
+		}
+		_ => (memory, 1),
+	};
+	number
+		.parse::<u64>()
+		.ok()
+		.and_then(|number| number.checked_mul(multiplier))
+		.ok_or_else(invalid)
+}
+
+/// Physical memory in MiB, from `/proc/meminfo` on Linux or `sysctl` on macOS.
+pub fn physical_memory() -> Option<u64> {
+	if let Ok(meminfo) = fs::read_to_string("/proc/meminfo") {
+		let line = meminfo.lines().find(|line| line.starts_with("MemTotal:"))?;
+		let kib: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+		return Some(kib / 1024);
+	}
+
+	let output = Command::new("sysctl")
+		.args(["-n", "hw.memsize"])
+		.output()
+		.ok()?;
+	let bytes: u64 = String::from_utf8_lossy(&output.stdout)
+		.trim()
+		.parse()
+		.ok()?;
+	Some(bytes / 1024 / 1024)
+}
+
+/// The part of a flag that identifies it, so flags with different values can be recognized as the same.
+/// `-Xmx4G` -> `-Xmx`, `-XX:+UseG1GC` -> `-XX:UseG1GC`, `-Dfoo=bar` -> `-Dfoo`.
+fn flag_key(flag: &str) -> String {
+	for prefix in ["-Xmx", "-Xms", "-Xss", "-Xmn"] {
+		if flag.starts_with(prefix) {
+			return prefix.to_string();
+		}
+	}
+	if let Some(option) = flag.strip_prefix("-XX:") {
+		let option = option.trim_start_matches(['+', '-']);
+		return format!("-XX:{}", option.split('=').next().unwrap());
+	}
+	if flag.starts_with("-D") {
+		return flag.split('=').next().unwrap().to_string();
+	}
+	flag.to_string()
+}
+
+impl JvmOptions {
+	/// The maximum heap size, either as configured or sized from physical memory.
+	pub fn max_memory(&self) -> Option<u64> {
+		if self.max_memory.is_some() || !self.auto_memory {
+			return self.max_memory;
+		}
+		let physical = physical_memory()?;
+		Some((physical / AUTO_MEMORY_FRACTION).clamp(AUTO_MEMORY_MIN, AUTO_MEMORY_MAX))
+	}
+
+	/// Check the options make sense for this machine and the major version of Java being used.
+	pub fn validate(&self, java_version: i32) -> Result<()> {
+		let max = self.max_memory();
+		if let Some(physical) = physical_memory() {
+			for requested in [self.min_memory, max].into_iter().flatten() {
+				if requested > physical {
+					return Err(Box::new(JvmOptionsError::HeapTooLarge {
+						requested,
+						physical,
+					}));
+				}
+			}
+		}
+		if let (Some(min), Some(max)) = (self.min_memory, max) {
+			if min > max {
+				return Err(Box::new(JvmOptionsError::MinAboveMax { min, max }));
+			}
+		}
+		let extra_gcs = self
+			.extra_args
+			.iter()
+			.filter_map(|arg| GcPreset::from_flag(arg));
+		for gc in self.gc.into_iter().chain(extra_gcs) {
+			if java_version < gc.min_java_version() {
+				return Err(Box::new(JvmOptionsError::UnsupportedGc {
+					gc,
+					java_version,
+				}));
+			}
+		}
+		for arg in &self.extra_args {
+			let key = flag_key(arg);
+			if let Some((_, removed_in)) = REMOVED_FLAGS
+				.iter()
+				.find(|(flag, removed_in)| *flag == key && java_version >= *removed_in)
+			{
+				return Err(Box::new(JvmOptionsError::RemovedFlag {
+					flag: arg.clone(),
+					removed_in: *removed_in,
+					java_version,
+				}));
+			}
+		}
+		Ok(())
+	}
+
+	/// All flags these options add to Java.
+	pub fn flags(&self, java_version: i32) -> Vec<String> {
+		let mut flags = Vec::new();
+		if let Some(min) = self.min_memory {
+			flags.push(format!("-Xms{min}M"));
+		}
+		if let Some(max) = self.max_memory() {
+			flags.push(format!("-Xmx{max}M"));
+		}
+		if let Some(gc) = self.gc {
+			flags.extend(gc.flags(java_version));
+		}
+		for arg in &self.extra_args {
+			// Java refuses to start with more than one collector, so the configured one wins.
+			let selects_gc = arg.starts_with("-XX:+Use") && arg.ends_with("GC");
+			if let Some(gc) = self
+				.gc
+				.filter(|gc| selects_gc && GcPreset::from_flag(arg) != Some(*gc))
+			{
+				warn!("Ignoring `{arg}`, since the instance uses the {gc} garbage collector");
+				continue;
+			}
+			flags.push(arg.clone());
+		}
+		flags
+	}
+
+	/// Add these options' flags to arguments from the version profile, skipping any flag that's already set.
+	pub fn merge_into(&self, args: &mut Vec<String>, java_version: i32) {
+		let mut existing: HashSet<String> = args.iter().map(|arg| flag_key(arg)).collect();
+		for flag in self.flags(java_version) {
+			// Values of flags like `-cp` are separate arguments, and shouldn't be deduplicated.
+			if !flag.starts_with('-') || existing.insert(flag_key(&flag)) {
+				args.push(flag);
+			}
+		}
+	}
+}
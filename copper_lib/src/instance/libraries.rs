@@ -0,0 +1,300 @@
+//! Picking one version of each library when profiles are combined, such as a mod loader's and vanilla's.
+
+use crate::structs::profile::Library;
+use std::{cmp::Ordering, collections::HashMap, error::Error, fmt};
+
+#[derive(Debug)]
+pub enum LibraryError {
+	/// The library was made by a mod loader's installer, and isn't in the store anymore.
+	Unavailable(String),
+}
+impl fmt::Display for LibraryError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Unavailable(name) => write!(
+				f,
+				"Library {name} can't be downloaded, and isn't in the store. Reinstall the mod loader"
+			),
+		}
+	}
+}
+impl Error for LibraryError {}
+
+/// A library that was left off the classpath because another version of it was kept.
+#[derive(Clone, Debug)]
+pub struct LibraryOverride {
+	pub kept: String,
+	pub dropped: String,
+	/// Whether `kept` won because it's from a profile closer to the child, rather than because it's newer.
+	pub by_inheritance: bool,
+}
+impl fmt::Display for LibraryOverride {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let reason = match self.by_inheritance {
+			true => "from the child profile",
+			false => "newer",
+		};
+		write!(f, "Using {} over {} ({reason})", self.kept, self.dropped)
+	}
+}
+
+/// Compare version strings part by part: numbers numerically, and anything else as text.
+/// A version with an extra qualifier (`1.0-beta`) is older than one without it (`1.0`), but one with an extra number (`1.0.1`) is newer.
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+	let parts = |version: &str| -> Vec<String> {
+		version
+			.split(['.', '-', '_', '+'])
+			.filter(|part| !part.is_empty())
+			.map(String::from)
+			.collect()
+	};
+	let (a, b) = (parts(a), parts(b));
+	for i in 0..a.len().max(b.len()) {
+		let ordering = match (a.get(i), b.get(i)) {
+			(Some(a), Some(b)) => match (a.parse::<u64>(), b.parse::<u64>()) {
+				(Ok(a), Ok(b)) => a.cmp(&b),
+				(Ok(_), Err(_)) => Ordering::Greater,
+				(Err(_), Ok(_)) => Ordering::Less,
+				(Err(_), Err(_)) => a.cmp(b),
+			},
+			(Some(a), None) => match a.parse::<u64>() {
+				Ok(_) => Ordering::Greater,
+				Err(_) => Ordering::Less,
+			},
+			(None, Some(b)) => match b.parse::<u64>() {
+				Ok(_) => Ordering::Less,
+				Err(_) => Ordering::Greater,
+			},
+			(None, None) => Ordering::Equal,
+		};
+		if ordering != Ordering::Equal {
+			return ordering;
+		}
+	}
+	Ordering::Equal
+}
+
+/// Whether `candidate` should replace `current`: libraries from closer to the child profile win, then newer versions.
+fn replaces(candidate: &Library, current: &Library, version: Ordering) -> bool {
+	match candidate.inheritance_depth.cmp(&current.inheritance_depth) {
+		Ordering::Less => true,
+		Ordering::Greater => false,
+		Ordering::Equal => version == Ordering::Greater,
+	}
+}
+
+/// Keep one active library for each group, artifact and classifier, in the position it first appeared.
+/// Libraries whose rules leave them out can't override anything, and libraries whose names aren't Maven coordinates are always kept.
+pub fn resolve(libraries: &[Library]) -> (Vec<Library>, Vec<LibraryOverride>) {
+	let mut resolved: Vec<Library> = Vec::new();
+	let mut overrides = Vec::new();
+	let mut positions = HashMap::new();
+
+	for library in libraries.iter().filter(|library| library.is_active()) {
+		let coordinate = match library.coordinate() {
+			Ok(coordinate) => coordinate,
+			Err(_) => {
+				resolved.push(library.clone());
+				continue;
+			}
+		};
+		let key = (
+			coordinate.group.clone(),
+			coordinate.artifact.clone(),
+			coordinate.classifier.clone(),
+		);
+		let index = match positions.get(&key) {
+			Some(index) => *index,
+			None => {
+				positions.insert(key, resolved.len());
+				resolved.push(library.clone());
+				continue;
+			}
+		};
+
+		let current = &resolved[index];
+		let current_version = current
+			.coordinate()
+			.map(|current| current.version)
+			.unwrap_or_default();
+		let version = compare_versions(&coordinate.version, &current_version);
+		// The same version listed twice isn't worth mentioning, but the child's entry is still preferred.
+		if version == Ordering::Equal {
+			if library.inheritance_depth < current.inheritance_depth {
+				resolved[index] = library.clone();
+			}
+			continue;
+		}
+		let by_inheritance = library.inheritance_depth != current.inheritance_depth;
+		if replaces(library, current, version) {
+			overrides.push(LibraryOverride {
+				kept: library.name.clone(),
+				dropped: current.name.clone(),
+				by_inheritance,
+			});
+			resolved[index] = library.clone();
+		} else {
+			overrides.push(LibraryOverride {
+				kept: current.name.clone(),
+				dropped: library.name.clone(),
+				by_inheritance,
+			});
+		}
+	}
+
+	(resolved, overrides)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// Libraries as they'd appear in a profile `depth` profiles up the inheritance chain.
+	fn libraries(depth: usize, json: &str) -> Vec<Library> {
+		let mut libraries: Vec<Library> = serde_json::from_str(json).unwrap();
+		for library in &mut libraries {
+			library.inheritance_depth = depth;
+		}
+		libraries
+	}
+
+	fn names(libraries: &[Library]) -> Vec<&str> {
+		libraries
+			.iter()
+			.map(|library| library.name.as_str())
+			.collect()
+	}
+
+	#[test]
+	fn compares_versions() {
+		for (a, b, expected) in [
+			("1.0", "1.0", Ordering::Equal),
+			("9.2", "10.1", Ordering::Less),
+			("1.0.1", "1.0", Ordering::Greater),
+			("1.0-beta", "1.0", Ordering::Less),
+			("1.0-beta", "1.0-alpha", Ordering::Greater),
+			("0.16.10", "0.16.9", Ordering::Greater),
+			("9.7.1", "9.7", Ordering::Greater),
+			("2.0.0+build.1", "2.0.0", Ordering::Less),
+		] {
+			assert_eq!(compare_versions(a, b), expected, "{a} vs {b}");
+		}
+	}
+
+	#[test]
+	fn newest_duplicate_wins() {
+		let libraries = libraries(
+			0,
+			r#"[
+				{"name": "org.ow2.asm:asm:9.2"},
+				{"name": "com.google.guava:guava:31.1-jre"},
+				{"name": "org.ow2.asm:asm:9.7.1"},
+				{"name": "org.ow2.asm:asm:9.6"}
+			]"#,
+		);
+		let (resolved, overrides) = resolve(&libraries);
+		assert_eq!(
+			names(&resolved),
+			["org.ow2.asm:asm:9.7.1", "com.google.guava:guava:31.1-jre"]
+		);
+		let overrides: Vec<_> = overrides
+			.iter()
+			.map(|o| (o.kept.as_str(), o.dropped.as_str(), o.by_inheritance))
+			.collect();
+		assert_eq!(
+			overrides,
+			[
+				("org.ow2.asm:asm:9.7.1", "org.ow2.asm:asm:9.2", false),
+				("org.ow2.asm:asm:9.7.1", "org.ow2.asm:asm:9.6", false),
+			]
+		);
+	}
+
+	#[test]
+	fn child_profile_wins_over_newer_parent() {
+		let mut all = libraries(0, r#"[{"name": "org.ow2.asm:asm:9.6"}]"#);
+		all.extend(libraries(
+			1,
+			r#"[{"name": "org.ow2.asm:asm:9.7.1"}, {"name": "org.lwjgl:lwjgl:3.3.3"}]"#,
+		));
+		let (resolved, overrides) = resolve(&all);
+		assert_eq!(
+			names(&resolved),
+			["org.ow2.asm:asm:9.6", "org.lwjgl:lwjgl:3.3.3"]
+		);
+		assert_eq!(overrides.len(), 1);
+		assert!(overrides[0].by_inheritance);
+
+		// The same version in both is taken from the child, without being reported.
+		let mut all = libraries(1, r#"[{"name": "org.ow2.asm:asm:9.6", "url": "parent"}]"#);
+		all.extend(libraries(
+			0,
+			r#"[{"name": "org.ow2.asm:asm:9.6", "url": "child"}]"#,
+		));
+		let (resolved, overrides) = resolve(&all);
+		assert_eq!(resolved.len(), 1);
+		assert_eq!(resolved[0].url.as_deref(), Some("child"));
+		assert!(overrides.is_empty());
+	}
+
+	#[test]
+	fn native_classifiers_are_separate_libraries() {
+		let libraries = libraries(
+			0,
+			r#"[
+				{"name": "org.lwjgl:lwjgl:3.3.1"},
+				{"name": "org.lwjgl:lwjgl:3.3.1:natives-linux"},
+				{"name": "org.lwjgl:lwjgl:3.3.1:natives-windows"},
+				{"name": "org.lwjgl:lwjgl:3.3.3:natives-linux"},
+				{"name": "org.lwjgl:lwjgl:3.3.3"}
+			]"#,
+		);
+		let (resolved, overrides) = resolve(&libraries);
+		assert_eq!(
+			names(&resolved),
+			[
+				"org.lwjgl:lwjgl:3.3.3",
+				"org.lwjgl:lwjgl:3.3.3:natives-linux",
+				"org.lwjgl:lwjgl:3.3.1:natives-windows",
+			]
+		);
+		assert_eq!(overrides.len(), 2);
+	}
+
+	#[test]
+	fn inactive_libraries_are_left_out() {
+		let libraries = libraries(
+			0,
+			r#"[
+				{"name": "ca.weblite:java-objc-bridge:1.0.0", "rules": [
+					{"action": "allow", "os": {"name": "nowhere"}}
+				]},
+				{"name": "org.ow2.asm:asm:9.6"},
+				{"name": "org.ow2.asm:asm:9.7.1", "rules": [
+					{"action": "allow", "os": {"name": "nowhere"}}
+				]},
+				{"name": "org.lwjgl:lwjgl:3.3.3", "rules": [
+					{"action": "allow"},
+					{"action": "disallow", "os": {"name": "nowhere"}}
+				]}
+			]"#,
+		);
+		let (resolved, overrides) = resolve(&libraries);
+		assert_eq!(
+			names(&resolved),
+			["org.ow2.asm:asm:9.6", "org.lwjgl:lwjgl:3.3.3"]
+		);
+		assert!(overrides.is_empty());
+	}
+
+	#[test]
+	fn unparsable_names_are_kept() {
+		let libraries = libraries(
+			0,
+			r#"[{"name": "not-a-coordinate"}, {"name": "not-a-coordinate"}]"#,
+		);
+		let (resolved, overrides) = resolve(&libraries);
+		assert_eq!(resolved.len(), 2);
+		assert!(overrides.is_empty());
+	}
+}
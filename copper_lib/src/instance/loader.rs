@@ -0,0 +1,250 @@
+//! Mod loaders, installed by merging their version profile onto the vanilla one.
+//! Fabric and Quilt profiles come from their meta APIs, while Forge and NeoForge's are made by running their installers.
+
+use super::installer;
+use crate::{
+	api::{
+		fabric::{FabricMeta, LoaderEntry},
+		forge, mojang, quilt,
+	},
+	structs::Profile,
+	Paths, Result,
+};
+use serde::{Deserialize, Serialize};
+use std::{error::Error, fmt, str::FromStr};
+use tracing::info;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LoaderKind {
+	Fabric,
+	Quilt,
+	Forge,
+	NeoForge,
+}
+
+/// A mod loader for an instance, written as `loader[@version]`, e.g. `fabric` or `quilt@0.26.0`.
+/// Without a version (or with `@stable`), the instance tracks the latest stable version for its game version.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Loader {
+	pub kind: LoaderKind,
+	pub version: Option<String>,
+}
+
+#[derive(Debug)]
+pub enum LoaderError {
+	Unknown(String),
+	NoVersions {
+		loader: LoaderKind,
+		game_version: String,
+	},
+	UnknownVersion {
+		loader: LoaderKind,
+		version: String,
+		game_version: String,
+	},
+}
+impl fmt::Display for LoaderError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Unknown(loader) => {
+				write!(
+					f,
+					"Unknown mod loader `{loader}`, expected fabric, quilt, forge or neoforge"
+				)
+			}
+			Self::NoVersions {
+				loader,
+				game_version,
+			} => write!(
+				f,
+				"{loader} has no stable versions for Minecraft {game_version}"
+			),
+			Self::UnknownVersion {
+				loader,
+				version,
+				game_version,
+			} => write!(
+				f,
+				"{loader} {version} doesn't exist, or doesn't support Minecraft {game_version}"
+			),
+		}
+	}
+}
+impl Error for LoaderError {}
+
+impl fmt::Display for LoaderKind {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let name = match self {
+			Self::Fabric => "Fabric",
+			Self::Quilt => "Quilt",
+			Self::Forge => "Forge",
+			Self::NeoForge => "NeoForge",
+		};
+		write!(f, "{name}")
+	}
+}
+
+impl LoaderKind {
+	/// Loaders that mods for this one can declare, as mod providers name them. Quilt can also load Fabric mods.
+	pub fn mod_loaders(&self) -> &'static [&'static str] {
+		match self {
+			Self::Fabric => &["fabric"],
+			Self::Quilt => &["quilt", "fabric"],
+			Self::Forge => &["forge"],
+			Self::NeoForge => &["neoforge"],
+		}
+	}
+
+	/// Meta API of loaders that have one.
+	fn meta(&self) -> Option<FabricMeta> {
+		match self {
+			Self::Fabric => Some(FabricMeta::default()),
+			Self::Quilt => Some(quilt::meta()),
+			Self::Forge | Self::NeoForge => None,
+		}
+	}
+}
+
+impl Loader {
+	/// Loader versions that work with a game version, newest first. Only Fabric and Quilt have a meta API to list them.
+	pub async fn versions(&self, game_version: &str) -> Result<Vec<LoaderEntry>> {
+		match self.kind.meta() {
+			Some(meta) => meta.loader_versions(game_version).await,
+			None => Ok(Vec::new()),
+		}
+	}
+
+	fn no_versions(&self, game_version: &str) -> LoaderError {
+		LoaderError::NoVersions {
+			loader: self.kind,
+			game_version: game_version.to_string(),
+		}
+	}
+
+	/// The loader version to install: the pinned one if it exists for the game version, or the latest stable one.
+	/// Forge's latest stable version is its recommended one, falling back to the latest if none is recommended yet.
+	pub async fn resolve_version(&self, game_version: &str) -> Result<String> {
+		match self.kind {
+			LoaderKind::Forge => {
+				return match &self.version {
+					// Forge only lists promoted versions, so pinned ones are checked when their installer is fetched.
+					Some(pinned) => Ok(pinned.clone()),
+					None => Ok(forge::forge_version(game_version)
+						.await?
+						.ok_or_else(|| self.no_versions(game_version))?),
+				};
+			}
+			LoaderKind::NeoForge => {
+				let versions = forge::neoforge_versions(game_version).await?;
+				let found = match &self.version {
+					Some(pinned) => versions
+						.iter()
+						.find(|version| *version == pinned)
+						.ok_or_else(|| LoaderError::UnknownVersion {
+							loader: self.kind,
+							version: pinned.clone(),
+							game_version: game_version.to_string(),
+						})?,
+					None => versions
+						.iter()
+						.rev()
+						.find(|version| !version.contains('-'))
+						.ok_or_else(|| self.no_versions(game_version))?,
+				};
+				return Ok(found.clone());
+			}
+			LoaderKind::Fabric | LoaderKind::Quilt => {}
+		}
+
+		let versions = self.versions(game_version).await?;
+		let found = match &self.version {
+			Some(pinned) => versions
+				.iter()
+				.find(|entry| &entry.loader.version == pinned)
+				.ok_or_else(|| LoaderError::UnknownVersion {
+					loader: self.kind,
+					version: pinned.clone(),
+					game_version: game_version.to_string(),
+				})?,
+			None => versions
+				.iter()
+				.find(|entry| entry.loader.is_stable())
+				.ok_or_else(|| self.no_versions(game_version))?,
+		};
+		Ok(found.loader.version.clone())
+	}
+
+	/// Merge the loader's profile onto the vanilla one, running its installer first if it has one.
+	pub async fn install(&self, path: &Paths, vanilla: Profile) -> Result<Profile> {
+		// A pinned version's profile is already in the store (or the installer cache), so launching doesn't need the network.
+		let version = match &self.version {
+			Some(version) => {
+				info!("Using {} {version}", self.kind);
+				version.clone()
+			}
+			None => {
+				let version = self.resolve_version(&vanilla.id).await?;
+				info!("Using {} {version} (latest stable)", self.kind);
+				version
+			}
+		};
+		let profile = match self.kind.meta() {
+			Some(meta) => meta.profile(&vanilla.id, &version).await?,
+			None => {
+				let (repository, coordinate) = match self.kind {
+					LoaderKind::Forge => forge::forge_installer(&vanilla.id, &version),
+					_ => forge::neoforge_installer(&version),
+				};
+				installer::install(path, &vanilla, &repository, &coordinate).await?
+			}
+		};
+		mojang::resolve_profile(profile, &[], &[vanilla]).await
+	}
+}
+
+impl FromStr for Loader {
+	type Err = LoaderError;
+
+	fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+		let (kind, version) = match s.split_once('@') {
+			Some((kind, "stable")) => (kind, None),
+			Some((kind, version)) => (kind, Some(version.to_string())),
+			None => (s, None),
+		};
+		let kind = match kind.to_lowercase().as_str() {
+			"fabric" => LoaderKind::Fabric,
+			"quilt" => LoaderKind::Quilt,
+			"forge" => LoaderKind::Forge,
+			"neoforge" => LoaderKind::NeoForge,
+			_ => return Err(LoaderError::Unknown(kind.to_string())),
+		};
+		Ok(Self { kind, version })
+	}
+}
+impl TryFrom<String> for Loader {
+	type Error = LoaderError;
+
+	fn try_from(value: String) -> std::result::Result<Self, Self::Error> {
+		value.parse()
+	}
+}
+impl fmt::Display for Loader {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let kind = match self.kind {
+			LoaderKind::Fabric => "fabric",
+			LoaderKind::Quilt => "quilt",
+			LoaderKind::Forge => "forge",
+			LoaderKind::NeoForge => "neoforge",
+		};
+		match &self.version {
+			Some(version) => write!(f, "{kind}@{version}"),
+			None => write!(f, "{kind}"),
+		}
+	}
+}
+impl From<Loader> for String {
+	fn from(loader: Loader) -> Self {
+		loader.to_string()
+	}
+}
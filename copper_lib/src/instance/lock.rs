@@ -0,0 +1,397 @@
+//! `copper.lock`, recording exactly which mods, resource packs and shaders an instance has.
+//!
+//! The lockfile only changes when content is added, removed or deliberately updated, so an instance's directory can be
+//! committed to git, and `copper install` restores the same files for anyone, straight from the store.
+
+use super::mods::ModError;
+use crate::{
+	api::modrinth::{ProjectType, Version, VersionFile, SITE_URL},
+	store::{fetch_verified, is_stored, symlink, StoreOutput},
+	Result,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+	collections::BTreeMap,
+	error::Error,
+	fmt, fs,
+	path::{Path, PathBuf},
+};
+
+pub const LOCK_FILE: &str = "copper.lock";
+const LOCK_VERSION: u32 = 1;
+/// Disabled entries are renamed with this suffix, so the game skips them.
+pub const DISABLED_SUFFIX: &str = ".disabled";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ContentKind {
+	Mod,
+	ResourcePack,
+	Shader,
+}
+
+impl ContentKind {
+	/// The folder in the game directory this kind of content goes in.
+	pub fn directory(&self) -> &'static str {
+		match self {
+			Self::Mod => "mods",
+			Self::ResourcePack => "resourcepacks",
+			Self::Shader => "shaderpacks",
+		}
+	}
+
+	pub fn from_project_type(project_type: ProjectType) -> Option<Self> {
+		match project_type {
+			ProjectType::Mod => Some(Self::Mod),
+			ProjectType::Resourcepack => Some(Self::ResourcePack),
+			ProjectType::Shader => Some(Self::Shader),
+			_ => None,
+		}
+	}
+}
+
+/// Where an entry came from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProviderKind {
+	Modrinth,
+}
+
+/// One file in an instance, keyed by its slug in the lockfile.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct LockEntry {
+	pub kind: ContentKind,
+	pub provider: ProviderKind,
+	pub project_id: String,
+	pub version_id: String,
+	pub version_number: String,
+	pub file_name: String,
+	pub url: String,
+	pub sha512: String,
+	pub size: u64,
+	#[serde(default = "enabled")]
+	pub enabled: bool,
+	/// Installed because another entry needs it, rather than being added directly.
+	#[serde(default)]
+	pub dependency: bool,
+	/// Held at its locked version when updating.
+	#[serde(default)]
+	pub pinned: bool,
+}
+
+fn enabled() -> bool {
+	true
+}
+
+#[derive(Debug)]
+pub enum LockError {
+	UnsupportedVersion(u32),
+	/// An entry's file isn't the size the lockfile says, by file name.
+	SizeMismatch(String),
+}
+impl fmt::Display for LockError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::UnsupportedVersion(version) => write!(
+				f,
+				"{LOCK_FILE} is version {version}, but this copper only understands version {LOCK_VERSION}"
+			),
+			Self::SizeMismatch(file) => {
+				write!(f, "{file} isn't the size {LOCK_FILE} says it is")
+			}
+		}
+	}
+}
+impl Error for LockError {}
+
+/// The lockfile, saved as `copper.lock` in the instance's directory.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Lockfile {
+	pub version: u32,
+	#[serde(default)]
+	pub entries: BTreeMap<String, LockEntry>,
+}
+
+impl Default for Lockfile {
+	fn default() -> Self {
+		Self {
+			version: LOCK_VERSION,
+			entries: BTreeMap::new(),
+		}
+	}
+}
+
+impl Lockfile {
+	pub fn path(directory: &Path) -> PathBuf {
+		directory.join(LOCK_FILE)
+	}
+
+	/// Load an instance's lockfile, or an empty one if it doesn't have one yet.
+	pub fn load(directory: &Path) -> Result<Self> {
+		let file = Self::path(directory);
+		if !file.exists() {
+			return Ok(Self::default());
+		}
+		let lock: Self = toml::from_str(&fs::read_to_string(file)?)?;
+		if lock.version != LOCK_VERSION {
+			return Err(Box::new(LockError::UnsupportedVersion(lock.version)));
+		}
+		Ok(lock)
+	}
+
+	pub fn save(&self, directory: &Path) -> Result<()> {
+		fs::write(Self::path(directory), toml::to_string(self)?)?;
+		Ok(())
+	}
+
+	/// Find an entry by its slug or project ID, returning its slug.
+	pub fn find(&self, name: &str) -> Result<String> {
+		self.entries
+			.iter()
+			.find(|(slug, entry)| *slug == name || entry.project_id == name)
+			.map(|(slug, _)| slug.clone())
+			.ok_or_else(|| Box::new(ModError::NotInstalled(name.to_string())).into())
+	}
+
+	/// Link every entry into place, and remove links to the store that aren't in the lockfile anymore. Files that weren't
+	/// linked by copper are left alone.
+	/// Everything is fetched into the store before any links change, so a failed download leaves the instance as it was.
+	pub async fn install(&self, directory: &Path) -> Result<()> {
+		for entry in self.entries.values() {
+			entry.fetch().await?;
+		}
+		for entry in self.entries.values() {
+			entry.link(directory).await?;
+		}
+		for kind in [
+			ContentKind::Mod,
+			ContentKind::ResourcePack,
+			ContentKind::Shader,
+		] {
+			let folder = directory.join(kind.directory());
+			if !folder.exists() {
+				continue;
+			}
+			for file in fs::read_dir(folder)? {
+				let path = file?.path();
+				let name = path.file_name().unwrap().to_string_lossy();
+				let name = name.trim_end_matches(DISABLED_SUFFIX);
+				let locked = self
+					.entries
+					.values()
+					.any(|entry| entry.kind == kind && entry.file_name == name);
+				if !locked && is_stored(&path) {
+					fs::remove_file(path)?;
+				}
+			}
+		}
+		Ok(())
+	}
+}
+
+impl LockEntry {
+	/// An entry for a Modrinth version's primary file.
+	pub fn from_modrinth(kind: ContentKind, version: &Version) -> Result<Self> {
+		let file = version
+			.primary_file()
+			.ok_or_else(|| ModError::NoFiles(version.id.clone()))?;
+		Ok(Self::from_modrinth_file(kind, version, file))
+	}
+
+	/// An entry for one of a Modrinth version's files.
+	pub fn from_modrinth_file(kind: ContentKind, version: &Version, file: &VersionFile) -> Self {
+		Self {
+			kind,
+			provider: ProviderKind::Modrinth,
+			project_id: version.project_id.clone(),
+			version_id: version.id.clone(),
+			version_number: version.version_number.clone(),
+			file_name: file.filename.clone(),
+			url: file.url.clone(),
+			sha512: file.hashes.sha512.clone(),
+			size: file.size,
+			enabled: true,
+			dependency: false,
+			pinned: false,
+		}
+	}
+
+	/// The entry's version page on its provider's website, which has its changelog.
+	pub fn page_url(&self, slug: &str) -> String {
+		match self.provider {
+			ProviderKind::Modrinth => {
+				let project_type = match self.kind {
+					ContentKind::Mod => "mod",
+					ContentKind::ResourcePack => "resourcepack",
+					ContentKind::Shader => "shader",
+				};
+				format!(
+					"{SITE_URL}/{project_type}/{slug}/version/{}",
+					self.version_id
+				)
+			}
+		}
+	}
+
+	/// Where the entry's link goes in an instance's directory, depending on whether it's enabled.
+	pub fn path(&self, directory: &Path, enabled: bool) -> PathBuf {
+		let folder = directory.join(self.kind.directory());
+		match enabled {
+			true => folder.join(&self.file_name),
+			false => folder.join(format!("{}{DISABLED_SUFFIX}", self.file_name)),
+		}
+	}
+
+	/// Fetch the file into the store, checking it's exactly the locked file.
+	pub async fn fetch(&self) -> Result<StoreOutput> {
+		let output = fetch_verified(self.sha512.clone(), self.url.clone()).await?;
+		self.check_size(&output)?;
+		Ok(output)
+	}
+
+	/// The sha512 already pins the file down, so a different size means the entry itself was edited wrongly.
+	fn check_size(&self, output: &StoreOutput) -> Result<()> {
+		if fs::metadata(&output.path)?.len() != self.size {
+			return Err(Box::new(LockError::SizeMismatch(self.file_name.clone())));
+		}
+		Ok(())
+	}
+
+	/// Fetch the file into the store, checking it's exactly the locked file, and link it into place, named for whether it's enabled.
+	pub async fn link(&self, directory: &Path) -> Result<()> {
+		let output = self.fetch().await?;
+		symlink(&output, &self.path(directory, self.enabled))?;
+		let other = self.path(directory, !self.enabled);
+		if other.symlink_metadata().is_ok() {
+			fs::remove_file(other)?;
+		}
+		Ok(())
+	}
+
+	/// Remove the entry's link. Its file stays in the store.
+	pub fn unlink(&self, directory: &Path) -> Result<()> {
+		for enabled in [true, false] {
+			let path = self.path(directory, enabled);
+			if path.symlink_metadata().is_ok() {
+				fs::remove_file(path)?;
+			}
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::env;
+
+	fn entry(file_name: &str, size: u64) -> LockEntry {
+		LockEntry {
+			kind: ContentKind::Mod,
+			provider: ProviderKind::Modrinth,
+			project_id: "AANobbMI".into(),
+			version_id: "OihdIimA".into(),
+			version_number: "mc1.20.1-0.5.3".into(),
+			file_name: file_name.into(),
+			url: format!("https://cdn.modrinth.com/data/AANobbMI/versions/OihdIimA/{file_name}"),
+			sha512: "00".repeat(64),
+			size,
+			enabled: true,
+			dependency: false,
+			pinned: false,
+		}
+	}
+
+	#[test]
+	fn saves_and_loads() {
+		let directory = env::temp_dir().join(format!("copper-lock-{}", std::process::id()));
+		fs::create_dir_all(&directory).unwrap();
+
+		// Instances without a lockfile have an empty one.
+		let empty = Lockfile::load(&directory).unwrap();
+		assert_eq!(empty.version, LOCK_VERSION);
+		assert!(empty.entries.is_empty());
+
+		let mut lock = Lockfile::default();
+		lock.entries.insert(
+			"sodium".into(),
+			entry("sodium-fabric-mc1.20.1-0.5.3.jar", 1024),
+		);
+		let mut disabled = entry("iris-mc1.20.1-1.6.10.jar", 2048);
+		disabled.kind = ContentKind::Shader;
+		disabled.enabled = false;
+		disabled.dependency = true;
+		disabled.pinned = true;
+		lock.entries.insert("iris".into(), disabled);
+		lock.save(&directory).unwrap();
+
+		let loaded = Lockfile::load(&directory).unwrap();
+		assert_eq!(loaded.version, LOCK_VERSION);
+		assert_eq!(
+			loaded.entries.keys().collect::<Vec<_>>(),
+			["iris", "sodium"]
+		);
+		let iris = &loaded.entries["iris"];
+		assert_eq!(iris.kind, ContentKind::Shader);
+		assert_eq!(iris.size, 2048);
+		assert!(!iris.enabled && iris.dependency && iris.pinned);
+		let sodium = &loaded.entries["sodium"];
+		assert_eq!(sodium.file_name, "sodium-fabric-mc1.20.1-0.5.3.jar");
+		assert!(sodium.enabled && !sodium.dependency && !sodium.pinned);
+		assert_eq!(sodium.sha512, lock.entries["sodium"].sha512);
+		assert_eq!(loaded.find("sodium").unwrap(), "sodium");
+		assert_eq!(loaded.find("AANobbMI").unwrap(), "iris");
+		assert!(loaded.find("lithium").is_err());
+
+		// Flags that are left out have their defaults.
+		fs::write(
+			Lockfile::path(&directory),
+			toml::to_string(&lock)
+				.unwrap()
+				.lines()
+				.filter(|line| !line.starts_with("enabled") && !line.starts_with("pinned"))
+				.collect::<Vec<_>>()
+				.join("\n"),
+		)
+		.unwrap();
+		let loaded = Lockfile::load(&directory).unwrap();
+		assert!(loaded.entries["iris"].enabled && !loaded.entries["iris"].pinned);
+
+		fs::remove_dir_all(&directory).unwrap();
+	}
+
+	#[test]
+	fn rejects_other_versions() {
+		let directory = env::temp_dir().join(format!("copper-lock-version-{}", std::process::id()));
+		fs::create_dir_all(&directory).unwrap();
+		fs::write(Lockfile::path(&directory), "version = 2\n").unwrap();
+
+		let error = Lockfile::load(&directory).unwrap_err();
+		assert!(matches!(
+			error.downcast_ref::<LockError>(),
+			Some(LockError::UnsupportedVersion(2))
+		));
+
+		fs::remove_dir_all(&directory).unwrap();
+	}
+
+	#[test]
+	fn checks_sizes() {
+		let directory = env::temp_dir().join(format!("copper-lock-size-{}", std::process::id()));
+		fs::create_dir_all(&directory).unwrap();
+		let output = StoreOutput {
+			path: directory.join("file"),
+			sha512: "00".repeat(64),
+		};
+		fs::write(&output.path, [0; 16]).unwrap();
+
+		assert!(entry("sodium.jar", 16).check_size(&output).is_ok());
+		let error = entry("sodium.jar", 17).check_size(&output).unwrap_err();
+		assert!(matches!(
+			error.downcast_ref::<LockError>(),
+			Some(LockError::SizeMismatch(file)) if file == "sodium.jar"
+		));
+
+		fs::remove_dir_all(&directory).unwrap();
+	}
+}
@@ -0,0 +1,346 @@
+//! Parsing log events that log4j's XML layouts write to stdout.
+//!
+//! Events look like this, and can span multiple lines:
+//! ```xml
+//! <log4j:Event logger="net.minecraft.client.Minecraft" timestamp="1686000000000" level="INFO" thread="Render thread">
+//!   <log4j:Message><![CDATA[Setting user: Player]]></log4j:Message>
+//! </log4j:Event>
+//! ```
+
+use chrono::{Local, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::{error::Error, fmt, str::FromStr};
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum LogLevel {
+	Trace,
+	Debug,
+	Info,
+	Warn,
+	Error,
+	Fatal,
+}
+impl fmt::Display for LogLevel {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let level = match self {
+			Self::Trace => "TRACE",
+			Self::Debug => "DEBUG",
+			Self::Info => "INFO",
+			Self::Warn => "WARN",
+			Self::Error => "ERROR",
+			Self::Fatal => "FATAL",
+		};
+		write!(f, "{level}")
+	}
+}
+
+#[derive(Debug)]
+pub struct UnknownLevel(String);
+impl fmt::Display for UnknownLevel {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "Unknown log level `{}`", self.0)
+	}
+}
+impl Error for UnknownLevel {}
+
+impl FromStr for LogLevel {
+	type Err = UnknownLevel;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s.to_uppercase().as_str() {
+			"TRACE" => Ok(Self::Trace),
+			"DEBUG" => Ok(Self::Debug),
+			"INFO" => Ok(Self::Info),
+			"WARN" | "WARNING" => Ok(Self::Warn),
+			"ERROR" => Ok(Self::Error),
+			"FATAL" => Ok(Self::Fatal),
+			_ => Err(UnknownLevel(s.to_string())),
+		}
+	}
+}
+
+/// A single log event.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct LogRecord {
+	/// Milliseconds since the Unix epoch.
+	pub timestamp: i64,
+	pub level: LogLevel,
+	pub thread: String,
+	pub logger: String,
+	pub message: String,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub throwable: Option<String>,
+}
+impl LogRecord {
+	/// Render the record like Minecraft's own console output: `[12:34:56] [Render thread/INFO] (Minecraft) Message`.
+	pub fn render(&self) -> String {
+		let time = match Local.timestamp_millis_opt(self.timestamp).single() {
+			Some(time) => time.format("%H:%M:%S").to_string(),
+			None => "??:??:??".into(),
+		};
+		let logger = self.logger.rsplit('.').next().unwrap_or_default();
+		let mut rendered = format!(
+			"[{time}] [{}/{}] ({logger}) {}",
+			self.thread, self.level, self.message
+		);
+		if let Some(throwable) = &self.throwable {
+			rendered.push('\n');
+			rendered.push_str(throwable.trim_end());
+		}
+		rendered
+	}
+}
+
+/// Output from the game, which is either a log event or a line that isn't part of one (like anything printed to stdout directly).
+#[derive(Clone, Debug)]
+pub enum LogLine {
+	Record(LogRecord),
+	Text(String),
+}
+
+/// Parses log events line by line, as they're printed.
+#[derive(Default)]
+pub struct LogParser {
+	buffer: Option<String>,
+}
+
+impl LogParser {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Feed a line of output to the parser. Returns whatever is complete after this line.
+	pub fn push_line(&mut self, line: &str) -> Vec<LogLine> {
+		let mut output = Vec::new();
+		let mut rest = line;
+
+		// Always runs at least once, so blank lines are kept, both on their own and inside events.
+		loop {
+			match &mut self.buffer {
+				None => match find_event_start(rest) {
+					Some(start) => {
+						if !rest[..start].trim().is_empty() {
+							output.push(LogLine::Text(rest[..start].to_string()));
+						}
+						self.buffer = Some(String::new());
+						rest = &rest[start..];
+					}
+					None => {
+						if !rest.is_empty() || line.is_empty() {
+							output.push(LogLine::Text(rest.to_string()));
+						}
+						break;
+					}
+				},
+				Some(buffer) => match find_event_end(rest) {
+					Some(end) => {
+						buffer.push_str(&rest[..end]);
+						match parse_event(buffer) {
+							Some(record) => output.push(LogLine::Record(record)),
+							None => output.push(LogLine::Text(buffer.clone())),
+						}
+						self.buffer = None;
+						rest = &rest[end..];
+						if rest.is_empty() {
+							break;
+						}
+					}
+					None => {
+						buffer.push_str(rest);
+						buffer.push('\n');
+						break;
+					}
+				},
+			}
+		}
+
+		output
+	}
+
+	/// Return anything left over from an event that never finished, for when the output ends.
+	pub fn finish(&mut self) -> Option<LogLine> {
+		self.buffer.take().map(LogLine::Text)
+	}
+}
+
+/// Log4j 2's newer `XMLLayout` doesn't use the `log4j:` prefix, so both forms are accepted.
+fn find_event_start(text: &str) -> Option<usize> {
+	["<log4j:Event ", "<Event "]
+		.iter()
+		.filter_map(|tag| text.find(tag))
+		.min()
+}
+
+/// Index just past the closing tag.
+fn find_event_end(text: &str) -> Option<usize> {
+	["</log4j:Event>", "</Event>"]
+		.iter()
+		.filter_map(|tag| text.find(tag).map(|index| index + tag.len()))
+		.min()
+}
+
+fn unescape(text: &str) -> String {
+	text.replace("&lt;", "<")
+		.replace("&gt;", ">")
+		.replace("&quot;", "\"")
+		.replace("&apos;", "'")
+		.replace("&#13;", "\r")
+		.replace("&#10;", "\n")
+		.replace("&amp;", "&")
+}
+
+/// Value of an attribute in the event's opening tag.
+fn attribute(tag: &str, name: &str) -> Option<String> {
+	let start = tag.find(&format!(" {name}=\""))? + name.len() + 3;
+	let end = start + tag[start..].find('"')?;
+	Some(unescape(&tag[start..end]))
+}
+
+/// Text content of a child element, with CDATA sections unwrapped.
+fn element(event: &str, name: &str) -> Option<String> {
+	let (open, close) = [
+		(format!("<log4j:{name}>"), format!("</log4j:{name}>")),
+		(format!("<{name}>"), format!("</{name}>")),
+	]
+	.into_iter()
+	.find(|(open, _)| event.contains(open.as_str()))?;
+
+	let start = event.find(&open)? + open.len();
+	let end = start + event[start..].find(&close)?;
+	let content = &event[start..end];
+
+	// Long messages can be split into multiple CDATA sections.
+	if content.contains("<![CDATA[") {
+		let mut text = String::new();
+		for section in content.split("<![CDATA[").skip(1) {
+			let (data, after) = section.split_once("]]>").unwrap_or((section, ""));
+			text.push_str(data);
+			text.push_str(&unescape(after));
+		}
+		Some(text)
+	} else {
+		Some(unescape(content))
+	}
+}
+
+fn parse_event(event: &str) -> Option<LogRecord> {
+	let tag = &event[..event.find('>')?];
+	Some(LogRecord {
+		timestamp: attribute(tag, "timestamp")
+			.or_else(|| attribute(tag, "timeMillis"))?
+			.parse()
+			.ok()?,
+		level: attribute(tag, "level")?.parse().ok()?,
+		thread: attribute(tag, "thread").unwrap_or_default(),
+		logger: attribute(tag, "logger")
+			.or_else(|| attribute(tag, "loggerName"))
+			.unwrap_or_default(),
+		message: element(event, "Message").unwrap_or_default(),
+		throwable: element(event, "Throwable"),
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{LogLevel, LogLine, LogParser};
+
+	/// Parse output line by line, like it's read from the game.
+	fn parse(output: &str) -> Vec<LogLine> {
+		let mut parser = LogParser::new();
+		let mut lines: Vec<LogLine> = output
+			.lines()
+			.flat_map(|line| parser.push_line(line))
+			.collect();
+		lines.extend(parser.finish());
+		lines
+	}
+
+	fn text(line: &LogLine) -> &str {
+		match line {
+			LogLine::Text(text) => text,
+			LogLine::Record(record) => panic!("expected text, got {record:?}"),
+		}
+	}
+
+	const LEGACY_XML: &str = r#"<log4j:Event logger="net.minecraft.client.Minecraft" timestamp="1686000000000" level="INFO" thread="Render thread">
+  <log4j:Message><![CDATA[Setting user: Player]]></log4j:Message>
+</log4j:Event>
+<log4j:Event logger="net.minecraft.server.MinecraftServer" timestamp="1686000001000" level="ERROR" thread="Server thread">
+  <log4j:Message><![CDATA[Something broke:
+
+twice]]></log4j:Message>
+  <log4j:Throwable><![CDATA[java.lang.RuntimeException: oops
+	at a.b.C.d(C.java:1)
+
+Caused by: java.lang.NullPointerException
+]]></log4j:Throwable>
+</log4j:Event>
+"#;
+
+	#[test]
+	fn parses_legacy_xml() {
+		let lines = parse(LEGACY_XML);
+		assert_eq!(lines.len(), 2);
+		let LogLine::Record(first) = &lines[0] else {
+			panic!("expected a record, got {:?}", lines[0]);
+		};
+		assert_eq!(first.timestamp, 1686000000000);
+		assert_eq!(first.level, LogLevel::Info);
+		assert_eq!(first.thread, "Render thread");
+		assert_eq!(first.logger, "net.minecraft.client.Minecraft");
+		assert_eq!(first.message, "Setting user: Player");
+		assert!(first.throwable.is_none());
+
+		let LogLine::Record(second) = &lines[1] else {
+			panic!("expected a record, got {:?}", lines[1]);
+		};
+		assert_eq!(second.level, LogLevel::Error);
+		assert_eq!(second.message, "Something broke:\n\ntwice");
+		assert_eq!(
+			second.throwable.as_deref(),
+			Some("java.lang.RuntimeException: oops\n\tat a.b.C.d(C.java:1)\n\nCaused by: java.lang.NullPointerException\n")
+		);
+	}
+
+	#[test]
+	fn parses_new_xml_layout() {
+		let lines = parse(
+			r#"<Event xmlns="http://logging.apache.org/log4j/2.0/events" timeMillis="1686000000000" thread="main" level="WARN" loggerName="com.example.Mod"><Message>a &amp; b</Message></Event><Event timeMillis="1686000000001" thread="main" level="DEBUG" loggerName="com.example.Mod"><Message>next</Message></Event>"#,
+		);
+		assert_eq!(lines.len(), 2);
+		let LogLine::Record(record) = &lines[0] else {
+			panic!("expected a record, got {:?}", lines[0]);
+		};
+		assert_eq!(record.level, LogLevel::Warn);
+		assert_eq!(record.logger, "com.example.Mod");
+		assert_eq!(record.message, "a & b");
+		assert!(matches!(&lines[1], LogLine::Record(record) if record.message == "next"));
+	}
+
+	#[test]
+	fn keeps_plain_output() {
+		let output = "[12:00:00] [main/INFO]: Loading\nException in thread \"main\" java.lang.Error\n\tat a.b.C.d(C.java:1)\n\nDone\n";
+		let lines = parse(output);
+		let texts: Vec<&str> = lines.iter().map(text).collect();
+		assert_eq!(texts, output.lines().collect::<Vec<_>>());
+	}
+
+	#[test]
+	fn separates_text_around_events() {
+		let lines = parse(
+			"printed <log4j:Event timestamp=\"1\" level=\"INFO\"><log4j:Message>hi</log4j:Message></log4j:Event> after",
+		);
+		assert_eq!(lines.len(), 3);
+		assert_eq!(text(&lines[0]), "printed ");
+		assert!(matches!(&lines[1], LogLine::Record(record) if record.message == "hi"));
+		assert_eq!(text(&lines[2]), " after");
+	}
+
+	#[test]
+	fn returns_unfinished_events() {
+		let lines = parse("<log4j:Event timestamp=\"1\" level=\"INFO\">\n  <log4j:Message>cut off");
+		assert_eq!(lines.len(), 1);
+		assert!(text(&lines[0]).contains("cut off"));
+	}
+}
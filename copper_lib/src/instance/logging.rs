@@ -0,0 +1,137 @@
+use crate::{
+	store::{fetch_sha1, symlink},
+	structs::Profile,
+	Paths, Result,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+	convert::Infallible,
+	fmt, fs,
+	path::{Path, PathBuf},
+	str::FromStr,
+};
+
+const XML_CONFIG: &str = include_str!("log4j/xml.xml");
+const PLAIN_CONFIG: &str = include_str!("log4j/plain.xml");
+
+/// Used for configs that aren't from a version profile. Versions that don't use log4j ignore it.
+const DEFAULT_ARGUMENT: &str = "-Dlog4j.configurationFile=${path}";
+
+/// Which log4j config Minecraft is launched with. Saved in the same format it's parsed from, see [LogConfig::from_str].
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(from = "String", into = "String")]
+pub enum LogConfig {
+	/// The config from the version profile, downloaded from Mojang.
+	#[default]
+	Profile,
+	/// Launcher-provided config that writes log events to stdout as XML.
+	Xml,
+	/// Launcher-provided config that writes log events to stdout as plain text.
+	Plain,
+	/// A config file somewhere else. Relative paths are relative to the instance's directory.
+	Custom(PathBuf),
+	/// Don't pass a config, leaving log4j with its defaults.
+	None,
+}
+
+impl FromStr for LogConfig {
+	type Err = Infallible;
+
+	/// `profile`, `xml`, `plain`, `none`, or otherwise a path to a config file.
+	fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+		Ok(match s {
+			"profile" => Self::Profile,
+			"xml" => Self::Xml,
+			"plain" => Self::Plain,
+			"none" => Self::None,
+			path => Self::Custom(PathBuf::from(path)),
+		})
+	}
+}
+
+impl fmt::Display for LogConfig {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Profile => write!(f, "profile"),
+			Self::Xml => write!(f, "xml"),
+			Self::Plain => write!(f, "plain"),
+			Self::Custom(path) => write!(f, "{}", path.display()),
+			Self::None => write!(f, "none"),
+		}
+	}
+}
+impl From<String> for LogConfig {
+	fn from(value: String) -> Self {
+		let Ok(config) = value.parse();
+		config
+	}
+}
+impl From<LogConfig> for String {
+	fn from(value: LogConfig) -> Self {
+		value.to_string()
+	}
+}
+
+impl LogConfig {
+	/// Where the config file is, or `None` if no config is used.
+	pub fn path(&self, path: &Paths, profile: &Profile, directory: &Path) -> Option<PathBuf> {
+		match self {
+			Self::Profile => {
+				let file = &profile.logging.as_ref()?.client.file;
+				let id = file.id.as_deref().unwrap_or("client.xml");
+				Some(path.assets.join("log_configs").join(id))
+			}
+			Self::Xml => Some(path.cache.join("log_configs").join("copper-xml.xml")),
+			Self::Plain => Some(path.cache.join("log_configs").join("copper-plain.xml")),
+			Self::Custom(custom) => Some(directory.join(custom)),
+			Self::None => None,
+		}
+	}
+
+	/// Whether log events are written to stdout as XML, which can be parsed with [LogParser](super::log_parser::LogParser).
+	/// Mojang's configs all use an XML layout for the console.
+	pub fn emits_xml(&self, profile: &Profile) -> bool {
+		match self {
+			Self::Profile => profile.logging.is_some(),
+			Self::Xml => true,
+			Self::Plain | Self::Custom(_) | Self::None => false,
+		}
+	}
+
+	/// The JVM argument pointing log4j at the config file.
+	pub fn argument(&self, path: &Paths, profile: &Profile, directory: &Path) -> Option<String> {
+		let config = self.path(path, profile, directory)?;
+		let argument = match (self, &profile.logging) {
+			(Self::Profile, Some(logging)) => logging.client.argument.as_str(),
+			_ => DEFAULT_ARGUMENT,
+		};
+		Some(argument.replace("${path}", config.to_str().unwrap()))
+	}
+
+	/// Make sure the config file exists, downloading it through the store or writing out the launcher-provided one.
+	pub async fn update(&self, path: &Paths, profile: &Profile, directory: &Path) -> Result<()> {
+		let config = match self.path(path, profile, directory) {
+			Some(config) => config,
+			None => return Ok(()),
+		};
+
+		let contents = match self {
+			Self::Profile => {
+				let file = &profile.logging.as_ref().unwrap().client.file;
+				let store = fetch_sha1(file.sha1.clone(), file.url.clone()).await?;
+				if fs::read_link(&config).ok() != Some(store.path.clone()) {
+					symlink(&store, &config)?;
+				}
+				return Ok(());
+			}
+			Self::Xml => XML_CONFIG,
+			Self::Plain => PLAIN_CONFIG,
+			Self::Custom(_) | Self::None => return Ok(()),
+		};
+		if fs::read_to_string(&config).ok().as_deref() != Some(contents) {
+			fs::create_dir_all(config.parent().unwrap())?;
+			fs::write(&config, contents)?;
+		}
+		Ok(())
+	}
+}
@@ -0,0 +1,163 @@
+//! Keeping track of what's been linked and extracted for an instance, so launches don't have to check everything again.
+
+use crate::{structs::Profile, Result};
+use data_encoding::HEXLOWER;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha512};
+use std::{
+	collections::{BTreeMap, BTreeSet, HashSet},
+	fs,
+	os::unix::fs::MetadataExt,
+	path::{Path, PathBuf},
+};
+use tracing::warn;
+
+const MANIFEST_FILE: &str = ".copper_materialized.json";
+
+/// Which store objects were linked and extracted for a version profile, saved in the instance's directory.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct Materialization {
+	/// sha512 of the profile, see [Materialization::profile_hash].
+	pub profile_hash: String,
+	/// Hashes of assets linked into `assets/objects`.
+	pub assets: BTreeSet<String>,
+	/// Files extracted into the natives directory, keyed by the URL of the jar they came from.
+	pub natives: BTreeMap<String, Vec<String>>,
+	/// Inodes of the files on the classpath, so a file that's been replaced is noticed too.
+	#[serde(default)]
+	pub libraries: BTreeMap<PathBuf, u64>,
+}
+
+impl Materialization {
+	pub fn new(profile_hash: String) -> Self {
+		Self {
+			profile_hash,
+			..Default::default()
+		}
+	}
+
+	/// A hash of everything in a profile, so any change to its libraries or assets is noticed.
+	pub fn profile_hash(profile: &Profile) -> Result<String> {
+		let mut hasher = Sha512::new();
+		hasher.update(serde_json::to_vec(profile)?);
+		Ok(HEXLOWER.encode(&hasher.finalize()))
+	}
+
+	pub fn path(directory: &Path) -> PathBuf {
+		directory.join(MANIFEST_FILE)
+	}
+
+	/// Load an instance's manifest. A missing or unreadable manifest just means everything gets checked again.
+	pub fn load(directory: &Path) -> Option<Self> {
+		let text = fs::read_to_string(Self::path(directory)).ok()?;
+		match serde_json::from_str(&text) {
+			Ok(manifest) => Some(manifest),
+			Err(err) => {
+				warn!("Ignoring invalid materialization manifest: {err}");
+				None
+			}
+		}
+	}
+
+	pub fn save(&self, directory: &Path) -> Result<()> {
+		fs::write(Self::path(directory), serde_json::to_string(self)?)?;
+		Ok(())
+	}
+
+	/// Record the inodes of the files on a classpath. Files that can't be read are left out, so they're linked again next time.
+	pub fn record_libraries(&mut self, classpath: &[PathBuf]) {
+		for path in classpath {
+			if let Ok(metadata) = fs::metadata(path) {
+				self.libraries.insert(path.clone(), metadata.ino());
+			}
+		}
+	}
+
+	/// Assets in the manifest whose files have gone missing.
+	pub fn drifted_assets(&self, objects: &Path) -> Vec<&str> {
+		self.assets
+			.iter()
+			.map(String::as_str)
+			.filter(|hash| !objects.join(&hash[..2]).join(hash).exists())
+			.collect()
+	}
+
+	/// Files on the classpath that have gone missing, or aren't the file that was linked anymore.
+	pub fn drifted_libraries(&self) -> HashSet<PathBuf> {
+		self.libraries
+			.iter()
+			.filter(|(path, inode)| {
+				fs::metadata(path).map(|metadata| metadata.ino()).ok() != Some(**inode)
+			})
+			.map(|(path, _)| path.clone())
+			.collect()
+	}
+
+	/// URLs of native jars in the manifest that have had some of their files go missing.
+	pub fn drifted_natives(&self, natives: &Path) -> HashSet<&str> {
+		self.natives
+			.iter()
+			.filter(|(_, files)| files.iter().any(|file| !natives.join(file).exists()))
+			.map(|(url, _)| url.as_str())
+			.collect()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::env;
+
+	#[test]
+	fn finds_drift() {
+		let directory =
+			env::temp_dir().join(format!("copper-materialization-{}", std::process::id()));
+		let objects = directory.join("objects");
+		let natives = directory.join("natives");
+		fs::create_dir_all(objects.join("ab")).unwrap();
+		fs::create_dir_all(&natives).unwrap();
+
+		let mut manifest = Materialization::new("hash".into());
+		for hash in ["ab01", "ab02"] {
+			fs::write(objects.join("ab").join(hash), hash).unwrap();
+			manifest.assets.insert(hash.into());
+		}
+		let (kept, replaced, deleted) = (
+			directory.join("kept.jar"),
+			directory.join("replaced.jar"),
+			directory.join("deleted.jar"),
+		);
+		for library in [&kept, &replaced, &deleted] {
+			fs::write(library, "jar").unwrap();
+		}
+		manifest.record_libraries(&[kept.clone(), replaced.clone(), deleted.clone()]);
+		fs::write(natives.join("liblwjgl.so"), "").unwrap();
+		manifest
+			.natives
+			.insert("lwjgl".into(), vec!["liblwjgl.so".into()]);
+		manifest
+			.natives
+			.insert("openal".into(), vec!["libopenal.so".into()]);
+
+		assert!(manifest.drifted_assets(&objects).is_empty());
+		assert!(manifest.drifted_libraries().is_empty());
+
+		fs::remove_file(objects.join("ab/ab02")).unwrap();
+		// Written next to it first, so the new file can't get the old one's inode.
+		fs::write(directory.join("new.jar"), "another jar").unwrap();
+		fs::rename(directory.join("new.jar"), &replaced).unwrap();
+		fs::remove_file(&deleted).unwrap();
+
+		assert_eq!(manifest.drifted_assets(&objects), ["ab02"]);
+		assert_eq!(
+			manifest.drifted_libraries(),
+			HashSet::from([replaced, deleted])
+		);
+		assert_eq!(
+			manifest.drifted_natives(&natives),
+			HashSet::from(["openal"])
+		);
+
+		fs::remove_dir_all(directory).unwrap();
+	}
+}
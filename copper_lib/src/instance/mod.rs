@@ -1,88 +1,389 @@
+pub mod command;
+pub mod config;
+pub mod crash;
 mod helpers;
+pub mod hooks;
+pub mod installer;
+pub mod jvm;
+pub mod libraries;
+pub mod loader;
+pub mod lock;
+pub mod log_parser;
+pub mod logging;
+pub mod materialization;
+pub mod mods;
+pub mod process;
+pub mod quick_play;
 
 use crate::{
 	api::mojang::get_asset_index,
+	java::{self, JavaInstallation},
 	store::{fetch, update_file},
 	structs::{
-		profile::{Argument, Arguments, RuleValue},
-		AssetIndex, Profile,
+		profile::{Argument, Arguments, Download, RuleValue},
+		Features, Profile,
 	},
 	Paths, Result,
 };
-use helpers::generate_classpath;
+use command::LaunchCommand;
+use config::InstanceConfig;
+use crash::CrashAnalysis;
+use helpers::{
+	fetch_natives, generate_classpath, join_classpath, native_downloads, planned_classpath,
+	relink_classpath,
+};
+use materialization::Materialization;
+use process::{GameExit, GameProcess};
+use quick_play::{QuickPlay, QuickPlayLogEntry};
 use std::{
+	collections::BTreeMap,
 	fs::{self, create_dir_all, File},
 	io,
-	path::PathBuf,
-	process::Command,
+	os::unix::fs::PermissionsExt,
+	path::{Path, PathBuf},
 };
 use tokio::task::JoinHandle;
 use tracing::info;
 use zip::ZipArchive;
 
 pub struct Instance {
-	asset_index: AssetIndex,
 	classpath: String,
+	config: InstanceConfig,
+	java: JavaInstallation,
 	profile: Profile,
 
+	name: String,
 	path: Paths,
+	directory: PathBuf,
 	natives: PathBuf,
+	quick_play: Option<QuickPlay>,
+	repair: bool,
 }
 
 impl Instance {
-	pub async fn new(path: &Paths, profile: Profile) -> Result<Self> {
-		let natives = path.natives.join(&profile.id);
-		create_dir_all(&natives)?;
+	/// An instance that has been saved with [InstanceConfig::save], running in its own directory.
+	/// Nothing is downloaded, linked or written until the instance is [updated](Self::update), so an instance can be
+	/// looked at (like for a dry run) without changing anything.
+	pub fn new(path: &Paths, name: &str, config: InstanceConfig, profile: Profile) -> Result<Self> {
+		let directory = InstanceConfig::directory(path, name);
+		Self::with_directory(path, name, directory, config, profile)
+	}
+
+	/// A version of Minecraft that isn't an instance, running in the config directory so no instance directory is made for it.
+	pub fn version(path: &Paths, config: InstanceConfig, profile: Profile) -> Result<Self> {
+		let name = config.version.clone();
+		Self::with_directory(path, &name, path.config.clone(), config, profile)
+	}
+
+	fn with_directory(
+		path: &Paths,
+		name: &str,
+		directory: PathBuf,
+		config: InstanceConfig,
+		profile: Profile,
+	) -> Result<Self> {
+		let java = java::locate(path, &profile.java_version);
+		config.jvm.validate(java.major_version)?;
+
 		Ok(Self {
-			asset_index: get_asset_index(&profile.asset_index).await?,
-			classpath: generate_classpath(&profile).await?,
+			classpath: join_classpath(&planned_classpath(path, &profile)?),
+			natives: path.natives.join(&profile.id),
+			config,
+			java,
 			profile,
 
+			name: name.to_string(),
 			path: path.clone(),
-			natives,
+			directory,
+			quick_play: None,
+			repair: false,
 		})
 	}
 
-	pub async fn launch(&self) -> Result<()> {
-		let asset_handles = self.update_assets().await?;
-		let native_handles = self.update_natives().await?;
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	/// The instance's game directory.
+	pub fn directory(&self) -> &PathBuf {
+		&self.directory
+	}
+
+	/// Launch straight into a world, server, or Realm. Versions older than 1.20 can only join servers.
+	pub fn set_quick_play(&mut self, quick_play: Option<QuickPlay>) -> Result<()> {
+		if let Some(quick_play) = &quick_play {
+			if !self.profile.supports_quick_play() {
+				quick_play.legacy_arguments()?;
+			}
+		}
+		self.quick_play = quick_play;
+		Ok(())
+	}
+
+	/// Link and check everything again when launching, instead of trusting the instance's [Materialization].
+	pub fn set_repair(&mut self, repair: bool) {
+		self.repair = repair;
+	}
+
+	/// Where Minecraft writes the outcome of quick play attempts.
+	pub fn quick_play_path(&self) -> PathBuf {
+		self.directory.join("quickPlay").join("log.json")
+	}
+
+	/// Read what Minecraft logged about quick play attempts, most recent last.
+	pub fn quick_play_log(&self) -> Result<Vec<QuickPlayLogEntry>> {
+		quick_play::read_log(&self.quick_play_path())
+	}
+
+	/// The quick play attempt of a game launched from this instance.
+	/// `None` if quick play wasn't requested, or Minecraft hasn't logged anything since the game started.
+	pub fn quick_play_result(&self, game: &GameProcess) -> Result<Option<QuickPlayLogEntry>> {
+		if self.quick_play.is_none() {
+			return Ok(None);
+		}
+		let path = self.quick_play_path();
+		match path.metadata().and_then(|metadata| metadata.modified()) {
+			Ok(modified) if modified >= game.started_at() => Ok(quick_play::read_log(&path)?.pop()),
+			_ => Ok(None),
+		}
+	}
+
+	/// Find out why a game launched from this instance crashed.
+	pub fn analyze_crash(&self, game: &GameProcess) -> Result<CrashAnalysis> {
+		crash::analyze(&self.directory, game.started_at(), &game.output_tail())
+	}
+
+	/// Whether the game's stdout is XML log events, see [LogConfig::emits_xml](logging::LogConfig::emits_xml).
+	pub fn emits_xml_logs(&self) -> bool {
+		self.config.logging.emits_xml(&self.profile)
+	}
+
+	/// Features that the version profile's argument rules are checked against.
+	pub fn features(&self) -> Features {
+		let mut features = Features::default();
+		if self.profile.supports_quick_play() {
+			features.has_quick_plays_support = true;
+			if let Some(quick_play) = &self.quick_play {
+				quick_play.apply_features(&mut features);
+			}
+		}
+		features
+	}
+
+	/// Install Java, and download and link any libraries, assets, natives, and logging config that are missing.
+	/// If the instance's [Materialization] matches the profile, only what has drifted from it (like files that were
+	/// deleted or replaced) is fixed, instead of going through the store for everything (see [Instance::set_repair]).
+	pub async fn update(&mut self) -> Result<()> {
+		// Find Java first, so a missing runtime or bad JVM options are reported before anything gets downloaded.
+		self.java = java::resolve(&self.path, &self.profile.java_version).await?;
+		self.config.jvm.validate(self.java.major_version)?;
+
+		create_dir_all(&self.directory)?;
+		create_dir_all(&self.natives)?;
+		let profile_hash = Materialization::profile_hash(&self.profile)?;
+		// Manifests from before libraries were recorded are no use either, since there's always at least the client jar.
+		let previous = Materialization::load(&self.directory).filter(|manifest| {
+			!self.repair && manifest.profile_hash == profile_hash && !manifest.libraries.is_empty()
+		});
+		let manifest = match &previous {
+			Some(previous) => self.repair_drift(previous).await?,
+			None => self.materialize(profile_hash).await?,
+		};
+
+		self.config
+			.logging
+			.update(&self.path, &self.profile, &self.directory)
+			.await?;
+		if previous.as_ref() != Some(&manifest) {
+			manifest.save(&self.directory)?;
+		}
+		Ok(())
+	}
+
+	/// Link and extract everything the profile needs, and record it all in a new [Materialization].
+	async fn materialize(&mut self, profile_hash: String) -> Result<Materialization> {
+		let (asset_index, classpath, ()) = tokio::try_join!(
+			get_asset_index(&self.profile.asset_index),
+			generate_classpath(&self.path, &self.profile),
+			fetch_natives(&self.profile),
+		)?;
+		self.classpath = join_classpath(&classpath);
+
+		let objects = self.path.assets.join("objects");
+		let assets: Vec<&str> = asset_index
+			.objects
+			.values()
+			.map(|entry| entry.hash.as_str())
+			.filter(|hash| !objects.join(&hash[..2]).join(hash).exists())
+			.collect();
+		let asset_handles = self.update_assets(&assets).await?;
+		let native_handles = self.update_natives(native_downloads(&self.profile));
+
+		let mut manifest = Materialization::new(profile_hash);
+		manifest.assets = asset_index
+			.objects
+			.values()
+			.map(|entry| entry.hash.clone())
+			.collect();
+		manifest.record_libraries(&classpath);
 		for handle in asset_handles {
 			handle.await?;
 		}
 		for handle in native_handles {
+			let (url, files) = handle.await?;
+			manifest.natives.insert(url, files);
+		}
+		Ok(manifest)
+	}
+
+	/// Fix only what has drifted from the instance's [Materialization].
+	async fn repair_drift(&self, previous: &Materialization) -> Result<Materialization> {
+		let assets = previous.drifted_assets(&self.path.assets.join("objects"));
+		let libraries = previous.drifted_libraries();
+		let drifted_natives = previous.drifted_natives(&self.natives);
+		let natives: Vec<Download> = native_downloads(&self.profile)
+			.into_iter()
+			.filter(|natives| drifted_natives.contains(natives.url.as_str()))
+			.collect();
+		if !(assets.is_empty() && libraries.is_empty() && natives.is_empty()) {
+			info!(
+				"Repairing {} assets, {} libraries and {} natives...",
+				assets.len(),
+				libraries.len(),
+				natives.len()
+			);
+		}
+
+		// Also brings back the asset index, if it's missing.
+		let asset_handles = self.update_assets(&assets).await?;
+		let native_handles = self.update_natives(natives);
+		relink_classpath(&self.path, &self.profile, &libraries).await?;
+
+		let mut manifest = previous.clone();
+		manifest.record_libraries(&libraries.into_iter().collect::<Vec<_>>());
+		for handle in asset_handles {
 			handle.await?;
 		}
+		for handle in native_handles {
+			let (url, files) = handle.await?;
+			manifest.natives.insert(url, files);
+		}
+		Ok(manifest)
+	}
 
-		let jvm_args = self.parse_jvm_arguments();
-		let game_args = self.parse_game_arguments(false, false);
+	/// Everything the game would be started with, without downloading or starting anything.
+	pub fn launch_command(&self) -> Result<LaunchCommand> {
+		let features = self.features();
+		Ok(LaunchCommand {
+			wrappers: self
+				.config
+				.wrappers
+				.iter()
+				.flat_map(|wrapper| wrapper.split_whitespace())
+				.map(|arg| self.expand_placeholders(arg))
+				.collect(),
+			java: self.java.path.clone(),
+			jvm_args: self.parse_jvm_arguments(),
+			main_class: self.profile.main_class.clone(),
+			game_args: self.parse_game_arguments(&features)?,
+			env: self.env(),
+			directory: self.directory.clone(),
+		})
+	}
+
+	/// Download anything missing and start the game. The game runs in the background, see [GameProcess].
+	pub async fn launch(&mut self) -> Result<GameProcess> {
+		self.update().await?;
+		let command = self.launch_command()?;
+		if self.features().has_quick_plays_support {
+			create_dir_all(self.quick_play_path().parent().unwrap())?;
+		}
+
+		if let Some(hook) = &self.config.hooks.pre_launch {
+			hooks::run(hook, &self.directory, &self.hook_env(None)).await?;
+		}
 
 		info!("Launching {}...", self.profile.id);
-		Command::new("java")
-			.current_dir(&self.path.config)
-			.args(jvm_args)
-			.args(game_args)
-			.spawn()?
-			.wait()?;
+		GameProcess::spawn(command.command(), &self.directory)
+	}
+
+	/// Download anything missing and write a shell script that starts the game without the launcher.
+	pub async fn export_script(&mut self, path: &Path) -> Result<()> {
+		self.update().await?;
+		let command = self.launch_command()?;
+		if self.features().has_quick_plays_support {
+			create_dir_all(self.quick_play_path().parent().unwrap())?;
+		}
 
+		let script = command.to_shell_script(
+			&format!("{} ({})", self.name, self.profile.id),
+			&self.copper_env(None),
+			self.config.hooks.pre_launch.as_deref(),
+			self.config.hooks.post_exit.as_deref(),
+		);
+		fs::write(path, script)?;
+		fs::set_permissions(path, fs::Permissions::from_mode(0o755))?;
 		Ok(())
 	}
 
-	pub async fn update_assets(&self) -> Result<Vec<JoinHandle<()>>> {
+	/// Run the instance's post-exit hook, if it has one.
+	pub async fn post_exit(&self, exit: &GameExit) -> Result<()> {
+		match &self.config.hooks.post_exit {
+			Some(hook) => hooks::run(hook, &self.directory, &self.hook_env(Some(exit))).await,
+			None => Ok(()),
+		}
+	}
+
+	/// The instance's extra environment variables, with placeholders expanded.
+	pub fn env(&self) -> BTreeMap<String, String> {
+		self.config
+			.env
+			.iter()
+			.map(|(key, value)| (key.clone(), self.expand_placeholders(value)))
+			.collect()
+	}
+
+	/// Environment variables for hooks: the instance's own, and [copper_env](Self::copper_env).
+	fn hook_env(&self, exit: Option<&GameExit>) -> BTreeMap<String, String> {
+		let mut env = self.env();
+		env.extend(self.copper_env(exit));
+		env
+	}
+
+	/// `COPPER_*` variables describing the instance, and how the game exited if it has.
+	fn copper_env(&self, exit: Option<&GameExit>) -> BTreeMap<String, String> {
+		let mut env = BTreeMap::new();
+		let mut set = |key: &str, value: String| env.insert(key.to_string(), value);
+		set("COPPER_INSTANCE_NAME", self.name.clone());
+		set(
+			"COPPER_INSTANCE_DIR",
+			self.directory.to_string_lossy().into(),
+		);
+		set("COPPER_VERSION", self.profile.id.clone());
+		set("COPPER_JAVA", self.java.path.to_string_lossy().into());
+		set("COPPER_JAVA_VERSION", self.java.major_version.to_string());
+		if let Some(exit) = exit {
+			let code = exit.code.map(|code| code.to_string()).unwrap_or_default();
+			set("COPPER_EXIT_CODE", code);
+			set("COPPER_RUNTIME_SECONDS", exit.runtime.as_secs().to_string());
+		}
+		env
+	}
+
+	/// Link assets (by hash) from the store, along with the asset index if it's missing.
+	pub async fn update_assets(&self, hashes: &[&str]) -> Result<Vec<JoinHandle<()>>> {
 		let mut handles = Vec::new();
 		let assets_root = self.path.assets.join("objects");
 
-		update_file(
-			None,
-			self.profile.asset_index.url.clone(),
-			&self.path.asset_indexes.join(format!(
-				"{}.json",
-				self.profile.asset_index.id.as_ref().unwrap()
-			)),
-		)
-		.await?;
-		for asset in &self.asset_index.objects {
-			let entry = asset.1;
-			let doublehash = format!("{}/{}", &entry.hash[..2], entry.hash);
+		let index = self.path.asset_indexes.join(format!(
+			"{}.json",
+			self.profile.asset_index.id.as_ref().unwrap()
+		));
+		if !hashes.is_empty() || !index.exists() {
+			update_file(None, self.profile.asset_index.url.clone(), &index).await?;
+		}
+		for hash in hashes {
+			let doublehash = format!("{}/{hash}", &hash[..2]);
 			let path = assets_root.join(&doublehash);
 			fs::create_dir_all(path.parent().unwrap())?;
 
@@ -100,86 +401,69 @@ impl Instance {
 		Ok(handles)
 	}
 
-	pub async fn update_natives(&self) -> Result<Vec<JoinHandle<()>>> {
+	/// Extract native jars into the natives directory. Each handle returns the jar's URL and the files it contains.
+	pub fn update_natives(&self, natives: Vec<Download>) -> Vec<JoinHandle<(String, Vec<String>)>> {
 		let mut handles = Vec::new();
 
-		for library in &self.profile.libraries {
-			if !library.is_active() {
-				continue;
-			}
-
-			let library = library.clone();
+		for natives in natives {
 			let natives_path = self.natives.clone();
 
 			handles.push(tokio::spawn(async move {
-				if !library.is_active() {
-					return;
-				}
-
-				if let Some(classifiers) = &library.downloads.classifiers {
-					// TODO make this less scuffed
-					let natives = match os_info::get().os_type() {
-						os_info::Type::Windows => match &classifiers.natives_windows {
-							Some(classifiers) => classifiers,
-							None => return,
-						},
-						os_info::Type::Macos => match &classifiers.natives_macos {
-							Some(classifiers) => classifiers,
-							None => return,
-						},
-						_ => match &classifiers.natives_linux {
-							Some(classifiers) => classifiers,
-							None => return,
-						},
-					};
-
-					let path = fetch(None, natives.url.clone()).await.unwrap().path;
-					let mut zip = ZipArchive::new(File::open(path).unwrap()).unwrap();
-					for i in 0..zip.len() {
-						let mut file = zip.by_index(i).unwrap();
-						let path = PathBuf::from(file.name());
-
-						let extension = path.extension();
-						if extension.is_none() {
-							continue;
-						}
-						let extension = extension.unwrap();
+				let mut files = Vec::new();
+				let path = fetch(None, natives.url.clone()).await.unwrap().path;
+				let mut zip = ZipArchive::new(File::open(path).unwrap()).unwrap();
+				for i in 0..zip.len() {
+					let mut file = zip.by_index(i).unwrap();
+					let path = PathBuf::from(file.name());
+
+					let extension = path.extension();
+					if extension.is_none() {
+						continue;
+					}
+					let extension = extension.unwrap();
 
-						if extension == "so" || extension == "dll" || extension == "dylib" {
-							let destination_path = natives_path.join(file.name());
-							if !destination_path.exists() {
-								let mut destination = File::create(destination_path).unwrap();
+					if extension == "so" || extension == "dll" || extension == "dylib" {
+						let destination_path = natives_path.join(file.name());
+						if !destination_path.exists() {
+							let mut destination = File::create(destination_path).unwrap();
 
-								io::copy(&mut file, &mut destination).unwrap();
-								info!("Extracted native {}", file.name());
-							}
+							io::copy(&mut file, &mut destination).unwrap();
+							info!("Extracted native {}", file.name());
 						}
+						files.push(file.name().to_string());
 					}
 				}
+				(natives.url, files)
 			}));
 		}
 
-		Ok(handles)
+		handles
 	}
 
 	// Minecraft/JVM arguments
-	pub fn parse_game_arguments(&self, demo: bool, custom_resolution: bool) -> Vec<String> {
+	pub fn parse_game_arguments(&self, features: &Features) -> Result<Vec<String>> {
 		let mut args = Vec::new();
 
 		match &self.profile.arguments {
 			Arguments::NewArguments(arguments) => {
-				self.parse_arguments_vec(&mut args, &arguments.game, demo, custom_resolution);
+				self.parse_arguments_vec(&mut args, &arguments.game, features);
 			}
 			Arguments::OldArguments(arguments) => {
 				let mut new_arguments = Vec::new();
 				for argument in arguments.split(' ') {
 					new_arguments.push(Argument::String(argument.into()));
 				}
-				self.parse_arguments_vec(&mut args, &new_arguments, demo, custom_resolution);
+				self.parse_arguments_vec(&mut args, &new_arguments, features);
 			}
 		}
 
-		args
+		if let Some(quick_play) = &self.quick_play {
+			if !features.has_quick_plays_support {
+				args.extend(quick_play.legacy_arguments()?);
+			}
+		}
+
+		Ok(args)
 	}
 
 	pub fn parse_jvm_arguments(&self) -> Vec<String> {
@@ -187,17 +471,25 @@ impl Instance {
 
 		match &self.profile.arguments {
 			Arguments::NewArguments(arguments) => {
-				self.parse_arguments_vec(&mut args, &arguments.jvm, false, false);
-				args.push(self.profile.main_class.clone());
+				self.parse_arguments_vec(&mut args, &arguments.jvm, &Features::default());
 			}
 			Arguments::OldArguments(_a) => {
 				let new_arguments = vec![Argument::String(
 					"-Djava.library.path=${natives_directory}".into(),
 				)];
-				self.parse_arguments_vec(&mut args, &new_arguments, false, false);
-				args.push(self.profile.main_class.clone());
+				self.parse_arguments_vec(&mut args, &new_arguments, &Features::default());
 			}
 		}
+		if let Some(argument) =
+			self.config
+				.logging
+				.argument(&self.path, &self.profile, &self.directory)
+		{
+			args.push(argument);
+		}
+		self.config
+			.jvm
+			.merge_into(&mut args, self.java.major_version);
 
 		args
 	}
@@ -206,14 +498,13 @@ impl Instance {
 		&self,
 		args: &mut Vec<String>,
 		arguments: &Vec<Argument>,
-		demo: bool,
-		custom_resolution: bool,
+		features: &Features,
 	) {
 		for argument in arguments {
 			match argument {
 				Argument::String(value) => self.parse_argument(args, value),
 				Argument::Rule(rule) => {
-					if rule.is_true(demo, custom_resolution) {
+					if rule.is_true(features) {
 						match &rule.value {
 							RuleValue::String(value) => self.parse_argument(args, value),
 							RuleValue::Vec(values) => {
@@ -229,17 +520,37 @@ impl Instance {
 	}
 
 	pub fn parse_argument(&self, args: &mut Vec<String>, arg: &str) {
+		args.push(self.expand_placeholders(arg));
+	}
+
+	/// Replace `${...}` placeholders, as used by version profiles, wrappers and environment variables.
+	pub fn expand_placeholders(&self, arg: &str) -> String {
 		let mut arg = arg.to_string();
 		arg = arg.replace("${assets_index_name}", &self.profile.assets);
 		arg = arg.replace("${assets_root}", self.path.assets.to_str().unwrap());
 		arg = arg.replace("${classpath}", &self.classpath);
-		arg = arg.replace("${game_directory}", self.path.config.to_str().unwrap());
+		arg = arg.replace("${game_directory}", self.directory.to_str().unwrap());
+		arg = arg.replace("${instance_name}", &self.name);
+		arg = arg.replace("${java}", self.java.path.to_str().unwrap());
+		arg = arg.replace("${classpath_separator}", ":");
 		arg = arg.replace("${launcher_name}", "Copper Launcher");
+		arg = arg.replace(
+			"${library_directory}",
+			self.path.libraries.to_str().unwrap(),
+		);
 		arg = arg.replace("${launcher_version}", "v0.1.0");
 		arg = arg.replace("${natives_directory}", self.natives.to_str().unwrap());
+		arg = arg.replace("${quickPlayPath}", self.quick_play_path().to_str().unwrap());
+		if let Some(quick_play) = &self.quick_play {
+			arg = match quick_play {
+				QuickPlay::Singleplayer(world) => arg.replace("${quickPlaySingleplayer}", world),
+				QuickPlay::Multiplayer(address) => arg.replace("${quickPlayMultiplayer}", address),
+				QuickPlay::Realms(realm) => arg.replace("${quickPlayRealms}", realm),
+			};
+		}
 		arg = arg.replace("${version_name}", &self.profile.id);
 		arg = arg.replace("${version_type}", &self.profile.version_type);
 
-		args.push(arg);
+		arg
 	}
 }
@@ -0,0 +1,718 @@
+//! Mods, resource packs and shaders from Modrinth. Their files stay in the store, and are symlinked into the instance's
+//! `mods`, `resourcepacks` and `shaderpacks` folders, as recorded in its [lockfile](super::lock).
+//! Jars that were put in `mods` by hand can be [scanned](scan) to find them on Modrinth.
+
+use super::{
+	loader::Loader,
+	lock::{ContentKind, LockEntry, Lockfile, ProviderKind, DISABLED_SUFFIX},
+};
+use crate::{
+	api::modrinth::{DependencyType, Modrinth, Project, Version, VersionType},
+	resolver::{resolve_with, Candidate, Dependency, DependencyKind, Provider},
+	store::import,
+	structs::mod_metadata::{FabricModJson, McmodInfo, ModsToml, QuiltModJson},
+	Result,
+};
+use serde::de::DeserializeOwned;
+use std::{
+	collections::{BTreeSet, HashMap, HashSet},
+	error::Error,
+	fmt,
+	fs::{self, File},
+	io::Read,
+	path::{Path, PathBuf},
+	str::FromStr,
+};
+use zip::ZipArchive;
+
+/// A project to look up on Modrinth, written as its slug or ID, optionally with a version (`sodium@mc1.20.1-0.5.3`).
+/// Modrinth URLs work too, including links to a specific version.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ModQuery {
+	pub project: String,
+	/// Version ID or version number.
+	pub version: Option<String>,
+}
+
+impl FromStr for ModQuery {
+	type Err = ModError;
+
+	fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+		if let Some(url) = s
+			.strip_prefix("https://")
+			.or_else(|| s.strip_prefix("http://"))
+		{
+			// Project URLs look like `modrinth.com/mod/<slug>[/version/<version>]`.
+			let mut segments = url.split('/').filter(|segment| !segment.is_empty()).skip(2);
+			let project = segments
+				.next()
+				.ok_or_else(|| ModError::InvalidQuery(s.to_string()))?;
+			let version = match (segments.next(), segments.next()) {
+				(Some("version"), Some(version)) => Some(version.to_string()),
+				_ => None,
+			};
+			return Ok(Self {
+				project: project.to_string(),
+				version,
+			});
+		}
+		let (project, version) = match s.split_once('@') {
+			Some((project, version)) => (project, Some(version.to_string())),
+			None => (s, None),
+		};
+		if project.is_empty() {
+			return Err(ModError::InvalidQuery(s.to_string()));
+		}
+		Ok(Self {
+			project: project.to_string(),
+			version,
+		})
+	}
+}
+
+#[derive(Debug)]
+pub enum ModError {
+	InvalidQuery(String),
+	NoLoader,
+	ProjectNotFound(String),
+	UnsupportedType(String),
+	NoFiles(String),
+	NotInstalled(String),
+	/// Held at its locked version, by name and version number.
+	Pinned(String, String),
+	/// Other installed projects require it, by name.
+	Needed {
+		name: String,
+		by: Vec<String>,
+	},
+}
+impl fmt::Display for ModError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::InvalidQuery(query) => write!(f, "`{query}` isn't a Modrinth slug, ID or URL"),
+			Self::NoLoader => write!(f, "The instance has no mod loader, see `--loader`"),
+			Self::ProjectNotFound(project) => {
+				write!(f, "There's no project `{project}` on Modrinth")
+			}
+			Self::UnsupportedType(project) => {
+				write!(f, "{project} isn't a mod, resource pack or shader")
+			}
+			Self::NoFiles(version) => write!(f, "Version {version} has no files"),
+			Self::NotInstalled(name) => write!(f, "{name} isn't installed"),
+			Self::Pinned(name, version) => write!(
+				f,
+				"{name} is pinned at {version}, unpin it first with `copper mod unpin`"
+			),
+			Self::Needed { name, by } => write!(
+				f,
+				"{name} is needed by {}. Remove them first, or use `--force`",
+				by.join(", ")
+			),
+		}
+	}
+}
+impl Error for ModError {}
+
+/// Projects on Modrinth that work with an instance's game version (and loader, for mods), for the [resolver](crate::resolver).
+pub struct ModrinthProvider<'a> {
+	pub modrinth: &'a Modrinth,
+	pub game_version: String,
+	pub loader: Option<Loader>,
+}
+
+impl<'a> ModrinthProvider<'a> {
+	pub fn new(modrinth: &'a Modrinth, game_version: &str, loader: Option<&Loader>) -> Self {
+		Self {
+			modrinth,
+			game_version: game_version.to_string(),
+			loader: loader.cloned(),
+		}
+	}
+
+	/// Look up a project by its slug or ID, along with what kind of content it is.
+	pub async fn project(&self, project: &str) -> Result<(Project, ContentKind)> {
+		let project = self
+			.modrinth
+			.project(project)
+			.await?
+			.ok_or_else(|| ModError::ProjectNotFound(project.to_string()))?;
+		match ContentKind::from_project_type(project.project_type) {
+			Some(kind) => Ok((project, kind)),
+			None => Err(Box::new(ModError::UnsupportedType(project.title))),
+		}
+	}
+
+	/// Projects of the versions that dependencies only name by version, looked up all at once.
+	async fn dependency_projects(&self, versions: &[Version]) -> Result<HashMap<String, String>> {
+		let ids: BTreeSet<String> = versions
+			.iter()
+			.flat_map(|version| &version.dependencies)
+			.filter(|dependency| dependency.project_id.is_none())
+			.filter_map(|dependency| dependency.version_id.clone())
+			.collect();
+		let ids: Vec<String> = ids.into_iter().collect();
+		Ok(self
+			.modrinth
+			.versions_by_ids(&ids)
+			.await?
+			.into_iter()
+			.map(|version| (version.id, version.project_id))
+			.collect())
+	}
+
+	/// A version as a candidate for the resolver. `projects` has the projects of dependencies that only name a version,
+	/// see [dependency_projects](Self::dependency_projects).
+	fn candidate(
+		slug: &str,
+		entry: LockEntry,
+		version: &Version,
+		projects: &HashMap<String, String>,
+	) -> Candidate<LockEntry> {
+		let mut dependencies = Vec::new();
+		for dependency in &version.dependencies {
+			// Some dependencies only name a version, and dependencies on files outside Modrinth can't be installed.
+			let project = match (&dependency.project_id, &dependency.version_id) {
+				(Some(project), _) => project.clone(),
+				(None, Some(version)) => match projects.get(version) {
+					Some(project) => project.clone(),
+					None => continue,
+				},
+				(None, None) => continue,
+			};
+			dependencies.push(Dependency {
+				project,
+				version: dependency.version_id.clone(),
+				kind: match dependency.dependency_type {
+					DependencyType::Required => DependencyKind::Required,
+					DependencyType::Optional => DependencyKind::Optional,
+					DependencyType::Incompatible => DependencyKind::Incompatible,
+					DependencyType::Embedded => DependencyKind::Embedded,
+				},
+			});
+		}
+		Candidate {
+			project: version.project_id.clone(),
+			version: version.id.clone(),
+			name: slug.to_string(),
+			version_number: version.version_number.clone(),
+			dependencies,
+			data: entry,
+		}
+	}
+
+	/// Locked entries from Modrinth, with their dependencies.
+	pub async fn installed(&self, lock: &Lockfile) -> Result<Vec<Candidate<LockEntry>>> {
+		let ids: Vec<String> = lock
+			.entries
+			.values()
+			.filter(|entry| entry.provider == ProviderKind::Modrinth)
+			.map(|entry| entry.version_id.clone())
+			.collect();
+		let versions = self.modrinth.versions_by_ids(&ids).await?;
+		let projects = self.dependency_projects(&versions).await?;
+		let mut installed = Vec::new();
+		for version in &versions {
+			let Some((slug, entry)) = lock
+				.entries
+				.iter()
+				.find(|(_, entry)| entry.version_id == version.id)
+			else {
+				continue;
+			};
+			installed.push(Self::candidate(slug, entry.clone(), version, &projects));
+		}
+		Ok(installed)
+	}
+}
+
+impl Provider for ModrinthProvider<'_> {
+	type Data = LockEntry;
+
+	/// Versions for the game version, releases first, then betas and alphas, each newest first.
+	/// Mods also have to support the instance's loader, while resource packs and shaders work with any.
+	async fn versions(&self, project: &str) -> Result<Vec<Candidate<LockEntry>>> {
+		let (project, kind) = self.project(project).await?;
+		let loaders: Vec<String> = match kind {
+			ContentKind::Mod => {
+				let loader = self.loader.as_ref().ok_or(ModError::NoLoader)?;
+				loader
+					.kind
+					.mod_loaders()
+					.iter()
+					.map(|loader| loader.to_string())
+					.collect()
+			}
+			ContentKind::ResourcePack | ContentKind::Shader => Vec::new(),
+		};
+		let mut versions = self
+			.modrinth
+			.versions(
+				&project.id,
+				std::slice::from_ref(&self.game_version),
+				&loaders,
+			)
+			.await?;
+		versions.sort_by_key(|version| version.version_type != VersionType::Release);
+
+		let projects = self.dependency_projects(&versions).await?;
+		let mut candidates = Vec::new();
+		for version in &versions {
+			let entry = LockEntry::from_modrinth(kind, version)?;
+			candidates.push(Self::candidate(&project.slug, entry, version, &projects));
+		}
+		Ok(candidates)
+	}
+}
+
+/// Jars in the `mods` folder that copper doesn't manage, like ones copied there by hand.
+pub fn unmanaged(directory: &Path, lock: &Lockfile) -> Result<Vec<PathBuf>> {
+	let folder = directory.join(ContentKind::Mod.directory());
+	if !folder.exists() {
+		return Ok(Vec::new());
+	}
+	let mut found = Vec::new();
+	for entry in fs::read_dir(folder)? {
+		let path = entry?.path();
+		let name = path.file_name().unwrap().to_string_lossy();
+		let jar = name.trim_end_matches(DISABLED_SUFFIX);
+		let locked = lock
+			.entries
+			.values()
+			.any(|entry| entry.kind == ContentKind::Mod && entry.file_name == jar);
+		if jar.ends_with(".jar") && !locked {
+			found.push(path);
+		}
+	}
+	found.sort();
+	Ok(found)
+}
+
+/// Names of installed versions that require a project.
+pub fn dependents(installed: &[Candidate<LockEntry>], project: &str) -> Vec<String> {
+	installed
+		.iter()
+		.filter(|candidate| {
+			candidate.dependencies.iter().any(|dependency| {
+				dependency.kind == DependencyKind::Required && dependency.project == project
+			})
+		})
+		.map(|candidate| candidate.name.clone())
+		.collect()
+}
+
+/// Names of the installed versions that `roots` (by name) need, directly or through each other, including the roots.
+pub fn dependency_closure<'a>(
+	installed: &[Candidate<LockEntry>],
+	roots: impl IntoIterator<Item = &'a str>,
+) -> BTreeSet<String> {
+	let mut needed = BTreeSet::new();
+	let mut queue: Vec<&Candidate<LockEntry>> = roots
+		.into_iter()
+		.filter_map(|root| installed.iter().find(|candidate| candidate.name == root))
+		.collect();
+	while let Some(candidate) = queue.pop() {
+		if !needed.insert(candidate.name.clone()) {
+			continue;
+		}
+		for dependency in &candidate.dependencies {
+			if dependency.kind != DependencyKind::Required {
+				continue;
+			}
+			queue.extend(
+				installed
+					.iter()
+					.find(|installed| installed.project == dependency.project),
+			);
+		}
+	}
+	needed
+}
+
+/// Names of the entries that updating just `only` changes: it, and the dependencies it needs.
+/// Other mods that were added directly stay as they are, even if `only` needs them.
+pub fn update_scope(
+	installed: &[Candidate<LockEntry>],
+	lock: &Lockfile,
+	only: &str,
+) -> BTreeSet<String> {
+	let needed = dependency_closure(installed, [only]);
+	lock.entries
+		.iter()
+		.filter(|(slug, entry)| *slug == only || (entry.dependency && needed.contains(*slug)))
+		.map(|(slug, _)| slug.clone())
+		.collect()
+}
+
+/// A provider that also offers held entries' locked versions, since they may not be listed anymore (like after the instance's
+/// game version changes).
+struct WithHeld<'a, 'b> {
+	provider: &'a ModrinthProvider<'b>,
+	held: Vec<Candidate<LockEntry>>,
+}
+
+impl Provider for WithHeld<'_, '_> {
+	type Data = LockEntry;
+
+	async fn versions(&self, project: &str) -> Result<Vec<Candidate<LockEntry>>> {
+		let mut versions = self.provider.versions(project).await?;
+		for held in &self.held {
+			let listed = versions
+				.iter()
+				.any(|candidate| candidate.version == held.version);
+			if held.project == project && !listed {
+				versions.push(held.clone());
+			}
+		}
+		Ok(versions)
+	}
+}
+
+/// Resolve the newest compatible versions of everything added to a lockfile directly, returning the new lockfile.
+/// Entries that `update` returns false for, like pinned ones, are held at their locked versions instead.
+/// Dependencies that aren't held are resolved again too, so ones that aren't needed anymore are left out.
+pub async fn resolve_update(
+	provider: &ModrinthProvider<'_>,
+	lock: &Lockfile,
+	update: impl Fn(&str, &LockEntry) -> bool,
+) -> Result<Lockfile> {
+	let mut requests = Vec::new();
+	let mut held = HashSet::new();
+	for (slug, entry) in &lock.entries {
+		if entry.provider != ProviderKind::Modrinth {
+			continue;
+		}
+		let hold = !update(slug, entry);
+		if hold {
+			held.insert(entry.version_id.clone());
+		}
+		if hold || !entry.dependency {
+			requests.push(Dependency {
+				project: entry.project_id.clone(),
+				version: hold.then(|| entry.version_id.clone()),
+				kind: DependencyKind::Required,
+			});
+		}
+	}
+	let held = match held.is_empty() {
+		true => Vec::new(),
+		false => provider
+			.installed(lock)
+			.await?
+			.into_iter()
+			.filter(|candidate| held.contains(&candidate.version))
+			.collect(),
+	};
+	let resolution = resolve_with(&WithHeld { provider, held }, &requests, &[]).await?;
+
+	let mut updated = Lockfile::default();
+	for candidate in resolution.install {
+		let mut entry = candidate.data;
+		let old = lock
+			.entries
+			.values()
+			.find(|old| old.project_id == entry.project_id);
+		entry.enabled = old.is_none_or(|old| old.enabled);
+		entry.dependency = old.is_none_or(|old| old.dependency);
+		entry.pinned = old.is_some_and(|old| old.pinned);
+		updated.entries.insert(candidate.name, entry);
+	}
+	Ok(updated)
+}
+
+/// What a mod jar says about itself, for jars that can't be found on Modrinth.
+#[derive(Clone, Debug)]
+pub struct ModMetadata {
+	/// The loader the metadata is for. Jars made for several loaders have the first one that's found.
+	pub loader: &'static str,
+	pub id: String,
+	pub name: Option<String>,
+	pub version: Option<String>,
+}
+
+impl fmt::Display for ModMetadata {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{}", self.name.as_ref().unwrap_or(&self.id))?;
+		if let Some(version) = &self.version {
+			write!(f, " {version}")?;
+		}
+		write!(f, " ({}, for {})", self.id, self.loader)
+	}
+}
+
+/// Read and parse a file in a jar, or `None` if it's missing or can't be parsed.
+fn read_json<T: DeserializeOwned>(zip: &mut ZipArchive<File>, name: &str) -> Option<T> {
+	let mut data = Vec::new();
+	zip.by_name(name).ok()?.read_to_end(&mut data).ok()?;
+	serde_json::from_slice(&data).ok()
+}
+
+fn read_toml<T: DeserializeOwned>(zip: &mut ZipArchive<File>, name: &str) -> Option<T> {
+	let mut data = String::new();
+	zip.by_name(name).ok()?.read_to_string(&mut data).ok()?;
+	toml::from_str(&data).ok()
+}
+
+/// Read a mod jar's metadata, from whichever of `fabric.mod.json`, `quilt.mod.json`, `META-INF/mods.toml` (or NeoForge's
+/// `META-INF/neoforge.mods.toml`) and `mcmod.info` it has. Jars with several mods have the first one's.
+pub fn read_metadata(jar: &Path) -> Result<Option<ModMetadata>> {
+	let mut zip = ZipArchive::new(File::open(jar)?)?;
+	if let Some(json) = read_json::<FabricModJson>(&mut zip, "fabric.mod.json") {
+		return Ok(Some(ModMetadata {
+			loader: "fabric",
+			id: json.id,
+			name: json.name,
+			version: Some(json.version),
+		}));
+	}
+	if let Some(json) = read_json::<QuiltModJson>(&mut zip, "quilt.mod.json") {
+		return Ok(Some(ModMetadata {
+			loader: "quilt",
+			id: json.quilt_loader.id,
+			name: json
+				.quilt_loader
+				.metadata
+				.and_then(|metadata| metadata.name),
+			version: Some(json.quilt_loader.version),
+		}));
+	}
+	for (file, loader) in [
+		("META-INF/neoforge.mods.toml", "neoforge"),
+		("META-INF/mods.toml", "forge"),
+	] {
+		let Some(toml) = read_toml::<ModsToml>(&mut zip, file) else {
+			continue;
+		};
+		if let Some(entry) = toml.mods.into_iter().next() {
+			return Ok(Some(ModMetadata {
+				loader,
+				id: entry.mod_id,
+				name: entry.display_name,
+				// Placeholders are filled in from the manifest when the game loads, so they aren't real versions.
+				version: entry.version.filter(|version| !version.starts_with("${")),
+			}));
+		}
+	}
+	if let Some(info) = read_json::<McmodInfo>(&mut zip, "mcmod.info") {
+		let (McmodInfo::List(mods) | McmodInfo::Object { mod_list: mods }) = info;
+		if let Some(entry) = mods.into_iter().next() {
+			return Ok(Some(ModMetadata {
+				loader: "forge",
+				id: entry.modid,
+				name: entry.name,
+				version: entry.version,
+			}));
+		}
+	}
+	Ok(None)
+}
+
+/// What [scan] did with a jar.
+#[derive(Clone, Debug)]
+pub enum Scanned {
+	/// Found on Modrinth, and added to the lockfile with this slug.
+	Locked(String),
+	/// Found on Modrinth, but a version of it is already in the lockfile under this slug, so the jar was left alone.
+	Duplicate(String),
+	/// Found on Modrinth, but the version isn't for the instance's game version or loader, so the jar was left alone.
+	Incompatible { slug: String, version: Version },
+	/// Not on Modrinth, with whatever metadata the jar has.
+	Unknown(Option<ModMetadata>),
+}
+
+/// Identify the jars in the `mods` folder that copper doesn't manage by their sha512, importing them into the store.
+/// Jars found on Modrinth are added to the lockfile and replaced with links to the store, if they're for the instance's game
+/// version and loader. Others are left where they are.
+/// Everything is looked up first, and the lockfile is saved before any jar is replaced, so a failed lookup changes nothing,
+/// and `copper install` never removes a link that was made here.
+pub async fn scan(
+	modrinth: &Modrinth,
+	directory: &Path,
+	game_version: &str,
+	loader: &Loader,
+	lock: &mut Lockfile,
+) -> Result<Vec<(PathBuf, Scanned)>> {
+	let mut jars = Vec::new();
+	for path in unmanaged(directory, lock)? {
+		let store = import(fs::read(&path)?)?;
+		jars.push((path, store.sha512));
+	}
+	let hashes: Vec<String> = jars.iter().map(|(_, sha512)| sha512.clone()).collect();
+	let versions = modrinth.versions_by_hashes(&hashes).await?;
+
+	let mut scanned = Vec::new();
+	let mut found_entries: Vec<(PathBuf, String, LockEntry)> = Vec::new();
+	for (path, sha512) in jars {
+		let found = versions.get(&sha512).and_then(|version| {
+			let file = version
+				.files
+				.iter()
+				.find(|file| file.hashes.sha512 == sha512)?;
+			Some((version, file))
+		});
+		let Some((version, file)) = found else {
+			let metadata = read_metadata(&path).unwrap_or(None);
+			scanned.push((path, Scanned::Unknown(metadata)));
+			continue;
+		};
+		// Jars earlier in this scan count too, so two versions of a mod aren't both locked.
+		let duplicate = lock
+			.entries
+			.iter()
+			.chain(found_entries.iter().map(|(_, slug, entry)| (slug, entry)))
+			.find(|(_, entry)| entry.project_id == version.project_id);
+		if let Some((slug, _)) = duplicate {
+			scanned.push((path, Scanned::Duplicate(slug.clone())));
+			continue;
+		}
+		let slug = match modrinth.project(&version.project_id).await? {
+			Some(project) => project.slug,
+			None => version.project_id.clone(),
+		};
+		let compatible = version
+			.game_versions
+			.iter()
+			.any(|game| game == game_version)
+			&& version
+				.loaders
+				.iter()
+				.any(|name| loader.kind.mod_loaders().contains(&name.as_str()));
+		if !compatible {
+			let version = version.clone();
+			scanned.push((path, Scanned::Incompatible { slug, version }));
+			continue;
+		}
+
+		let mut entry = LockEntry::from_modrinth_file(ContentKind::Mod, version, file);
+		entry.enabled = !path.to_string_lossy().ends_with(DISABLED_SUFFIX);
+		scanned.push((path.clone(), Scanned::Locked(slug.clone())));
+		found_entries.push((path, slug, entry));
+	}
+	if found_entries.is_empty() {
+		return Ok(scanned);
+	}
+
+	for (_, slug, entry) in &found_entries {
+		lock.entries.insert(slug.clone(), entry.clone());
+	}
+	lock.save(directory)?;
+	// The jars are in the store now, so they can be replaced with links, named as they are on Modrinth.
+	for (path, _, entry) in &found_entries {
+		entry.link(directory).await?;
+		if *path != entry.path(directory, entry.enabled) {
+			fs::remove_file(path)?;
+		}
+	}
+	Ok(scanned)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::{env, io::Write};
+	use zip::{write::FileOptions, ZipWriter};
+
+	fn jar(directory: &Path, name: &str, files: &[(&str, &str)]) -> PathBuf {
+		let path = directory.join(name);
+		let mut zip = ZipWriter::new(File::create(&path).unwrap());
+		for (file, contents) in files {
+			zip.start_file(*file, FileOptions::default()).unwrap();
+			zip.write_all(contents.as_bytes()).unwrap();
+		}
+		zip.finish().unwrap();
+		path
+	}
+
+	#[test]
+	fn reads_metadata() {
+		let directory = env::temp_dir().join(format!("copper-mods-{}", std::process::id()));
+		fs::create_dir_all(&directory).unwrap();
+		let read = |name: &str, files: &[(&str, &str)]| {
+			read_metadata(&jar(&directory, name, files))
+				.unwrap()
+				.map(|metadata| metadata.to_string())
+		};
+
+		assert_eq!(
+			read(
+				"sodium.jar",
+				&[(
+					"fabric.mod.json",
+					r#"{"schemaVersion": 1, "id": "sodium", "version": "0.5.3+mc1.20.1", "name": "Sodium"}"#
+				)]
+			)
+			.as_deref(),
+			Some("Sodium 0.5.3+mc1.20.1 (sodium, for fabric)")
+		);
+		assert_eq!(
+			read(
+				"qsl.jar",
+				&[(
+					"quilt.mod.json",
+					r#"{"schema_version": 1, "quilt_loader": {"id": "qsl", "version": "6.1.2", "metadata": {"name": "QSL"}}}"#
+				)]
+			)
+			.as_deref(),
+			Some("QSL 6.1.2 (qsl, for quilt)")
+		);
+		// Forge fills in placeholder versions from the manifest, so they're left out.
+		assert_eq!(
+			read(
+				"jei.jar",
+				&[(
+					"META-INF/mods.toml",
+					"modLoader = \"javafml\"\n[[mods]]\nmodId = \"jei\"\nversion = \"${file.jarVersion}\"\ndisplayName = \"Just Enough Items\"\n"
+				)]
+			)
+			.as_deref(),
+			Some("Just Enough Items (jei, for forge)")
+		);
+		// NeoForge's own file wins over the Forge one in jars that have both.
+		assert_eq!(
+			read(
+				"both.jar",
+				&[
+					("META-INF/mods.toml", "[[mods]]\nmodId = \"forge_side\"\n"),
+					(
+						"META-INF/neoforge.mods.toml",
+						"[[mods]]\nmodId = \"neo_side\"\nversion = \"1.0\"\n"
+					),
+				]
+			)
+			.as_deref(),
+			Some("neo_side 1.0 (neo_side, for neoforge)")
+		);
+		for (name, info) in [
+			(
+				"list.jar",
+				r#"[{"modid": "jei", "name": "JEI", "version": "4.16"}]"#,
+			),
+			(
+				"object.jar",
+				r#"{"modListVersion": 2, "modList": [{"modid": "jei", "name": "JEI", "version": "4.16"}]}"#,
+			),
+		] {
+			assert_eq!(
+				read(name, &[("mcmod.info", info)]).as_deref(),
+				Some("JEI 4.16 (jei, for forge)")
+			);
+		}
+		// Broken metadata is skipped in favour of the next kind.
+		assert_eq!(
+			read(
+				"broken.jar",
+				&[
+					("fabric.mod.json", "{"),
+					("mcmod.info", r#"[{"modid": "old"}]"#)
+				]
+			)
+			.as_deref(),
+			Some("old (old, for forge)")
+		);
+		assert_eq!(read("plain.jar", &[("a/B.class", "")]), None);
+
+		fs::write(directory.join("text.jar"), "not a zip").unwrap();
+		assert!(read_metadata(&directory.join("text.jar")).is_err());
+
+		fs::remove_dir_all(&directory).unwrap();
+	}
+}
@@ -0,0 +1,227 @@
+use crate::Result;
+use std::{
+	collections::VecDeque,
+	fs::{self, create_dir_all, File},
+	io,
+	os::unix::process::ExitStatusExt,
+	path::Path,
+	process::Stdio,
+	sync::{Arc, Mutex},
+	time::{Duration, Instant, SystemTime},
+};
+use tokio::{
+	io::{AsyncBufReadExt, AsyncRead, AsyncWriteExt, BufReader},
+	process::{Child, Command},
+	sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender},
+	task::JoinHandle,
+};
+use tracing::warn;
+
+/// How many previous logs are kept in an instance's `logs/copper` directory, besides `latest.log`.
+const KEPT_LOGS: usize = 4;
+/// How many lines of output are kept in memory for crash analysis.
+const TAIL_LINES: usize = 500;
+
+/// A line of output from the game.
+#[derive(Clone, Debug)]
+pub enum GameEvent {
+	Stdout(String),
+	Stderr(String),
+}
+
+/// How and when the game exited.
+#[derive(Clone, Debug)]
+pub struct GameExit {
+	/// `None` if the game was killed by a signal.
+	pub code: Option<i32>,
+	pub signal: Option<i32>,
+	pub runtime: Duration,
+}
+impl GameExit {
+	pub fn success(&self) -> bool {
+		self.code == Some(0)
+	}
+}
+
+/// A running instance of the game. Output is read in the background, and can be received with [GameProcess::next_event].
+pub struct GameProcess {
+	child: Child,
+	pid: u32,
+	started: Instant,
+	started_at: SystemTime,
+	events: UnboundedReceiver<GameEvent>,
+	writer: Option<JoinHandle<()>>,
+	tail: Arc<Mutex<VecDeque<String>>>,
+}
+
+/// Move `latest.log` to `1.log`, `1.log` to `2.log` and so on, dropping the oldest.
+fn rotate_logs(directory: &Path) -> io::Result<()> {
+	create_dir_all(directory)?;
+	for i in (1..KEPT_LOGS).rev() {
+		let from = directory.join(format!("{i}.log"));
+		if from.exists() {
+			fs::rename(from, directory.join(format!("{}.log", i + 1)))?;
+		}
+	}
+	let latest = directory.join("latest.log");
+	if latest.exists() {
+		fs::rename(latest, directory.join("1.log"))?;
+	}
+	Ok(())
+}
+
+/// Send every line from a stream as an event.
+fn read_lines<R>(
+	stream: R,
+	sender: UnboundedSender<GameEvent>,
+	event: fn(String) -> GameEvent,
+) -> JoinHandle<()>
+where
+	R: AsyncRead + Unpin + Send + 'static,
+{
+	tokio::spawn(async move {
+		let mut lines = BufReader::new(stream).lines();
+		loop {
+			match lines.next_line().await {
+				Ok(Some(line)) => {
+					if sender.send(event(line)).is_err() {
+						break;
+					}
+				}
+				Ok(None) => break,
+				Err(err) => {
+					warn!("Failed to read game output: {err}");
+					break;
+				}
+			}
+		}
+	})
+}
+
+impl GameProcess {
+	/// Start the game, writing its output to `logs/copper/latest.log` in the game directory.
+	pub fn spawn(mut command: Command, directory: &Path) -> Result<Self> {
+		let log_directory = directory.join("logs").join("copper");
+		rotate_logs(&log_directory)?;
+		let mut log = tokio::fs::File::from_std(File::create(log_directory.join("latest.log"))?);
+
+		let mut child = command
+			.current_dir(directory)
+			.stdin(Stdio::null())
+			.stdout(Stdio::piped())
+			.stderr(Stdio::piped())
+			.kill_on_drop(false)
+			.spawn()?;
+		let started = Instant::now();
+		let started_at = SystemTime::now();
+		let pid = child
+			.id()
+			.ok_or_else(|| io::Error::other("The game exited as soon as it started"))?;
+
+		let (raw_sender, mut raw_receiver) = unbounded_channel();
+		read_lines(
+			child.stdout.take().unwrap(),
+			raw_sender.clone(),
+			GameEvent::Stdout,
+		);
+		read_lines(child.stderr.take().unwrap(), raw_sender, GameEvent::Stderr);
+
+		// Output from both streams goes through one task, so lines are logged in the order they're read.
+		let (sender, events) = unbounded_channel();
+		let tail = Arc::new(Mutex::new(VecDeque::with_capacity(TAIL_LINES)));
+		let writer_tail = tail.clone();
+		let writer = tokio::spawn(async move {
+			while let Some(event) = raw_receiver.recv().await {
+				let line = match &event {
+					GameEvent::Stdout(line) | GameEvent::Stderr(line) => line,
+				};
+				// Flushed every line, so the log is complete even if copper itself is stopped.
+				let written = async {
+					log.write_all(format!("{line}\n").as_bytes()).await?;
+					log.flush().await
+				};
+				if let Err(err) = written.await {
+					warn!("Failed to write game log: {err}");
+				}
+				{
+					let mut tail = writer_tail.lock().unwrap();
+					if tail.len() == TAIL_LINES {
+						tail.pop_front();
+					}
+					tail.push_back(line.clone());
+				}
+				// The receiver being dropped just means nobody is listening to events anymore.
+				let _ = sender.send(event);
+			}
+		});
+
+		Ok(Self {
+			child,
+			pid,
+			started,
+			started_at,
+			events,
+			writer: Some(writer),
+			tail,
+		})
+	}
+
+	pub fn pid(&self) -> u32 {
+		self.pid
+	}
+
+	/// When the game was started.
+	pub fn started_at(&self) -> SystemTime {
+		self.started_at
+	}
+
+	/// The last lines of output from the game, from both stdout and stderr.
+	pub fn output_tail(&self) -> Vec<String> {
+		self.tail.lock().unwrap().iter().cloned().collect()
+	}
+
+	/// How long the game has been running for.
+	pub fn runtime(&self) -> Duration {
+		self.started.elapsed()
+	}
+
+	/// Wait for the next line of output. Returns `None` once the game has closed its output.
+	pub async fn next_event(&mut self) -> Option<GameEvent> {
+		self.events.recv().await
+	}
+
+	/// Ask the game to exit with `SIGTERM`, which lets it save and shut down cleanly. Does nothing if it's already exited.
+	pub fn terminate(&self) -> Result<()> {
+		// Once the game has been waited on, its PID could belong to another process, so it's only signalled while it's
+		// still our child.
+		let Some(pid) = self.child.id() else {
+			return Ok(());
+		};
+		// SAFETY: `kill` has no memory safety requirements, and `pid` belongs to our child process.
+		if unsafe { libc::kill(pid as libc::pid_t, libc::SIGTERM) } == -1 {
+			return Err(Box::new(io::Error::last_os_error()));
+		}
+		Ok(())
+	}
+
+	/// Forcibly stop the game with `SIGKILL`.
+	pub async fn kill(&mut self) -> Result<()> {
+		self.child.kill().await?;
+		Ok(())
+	}
+
+	/// Wait for the game to exit, and for all of its output to be logged.
+	pub async fn wait(&mut self) -> Result<GameExit> {
+		let status = self.child.wait().await?;
+		let runtime = self.started.elapsed();
+		if let Some(writer) = self.writer.take() {
+			writer.await?;
+		}
+
+		Ok(GameExit {
+			code: status.code(),
+			signal: status.signal(),
+			runtime,
+		})
+	}
+}
@@ -0,0 +1,138 @@
+use crate::{structs::Features, Result};
+use serde::{Deserialize, Serialize};
+use std::{error::Error, fmt, fs::File, path::Path};
+
+/// Where Minecraft should go straight after starting, skipping the title screen.
+#[derive(Clone, Debug)]
+pub enum QuickPlay {
+	/// Name of the world's folder in `saves/`.
+	Singleplayer(String),
+	/// Server address, as `host` or `host:port`. IPv6 addresses with a port are written as `[address]:port`.
+	Multiplayer(String),
+	/// ID of the Realm.
+	Realms(String),
+}
+
+#[derive(Debug)]
+pub enum QuickPlayError {
+	/// Versions before 1.20 can only join servers.
+	Unsupported(&'static str),
+	InvalidPort(String),
+	InvalidAddress(String),
+}
+impl fmt::Display for QuickPlayError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Unsupported(kind) => write!(
+				f,
+				"Quick play into a {kind} requires Minecraft 1.20 or newer"
+			),
+			Self::InvalidPort(address) => write!(f, "Invalid port in server address `{address}`"),
+			Self::InvalidAddress(address) => write!(f, "Invalid server address `{address}`"),
+		}
+	}
+}
+impl Error for QuickPlayError {}
+
+impl QuickPlay {
+	/// Sets the feature flags the version profile's quick play arguments are conditional on.
+	pub fn apply_features(&self, features: &mut Features) {
+		match self {
+			Self::Singleplayer(_) => features.is_quick_play_singleplayer = true,
+			Self::Multiplayer(_) => features.is_quick_play_multiplayer = true,
+			Self::Realms(_) => features.is_quick_play_realms = true,
+		}
+	}
+
+	/// Arguments for versions that don't support quick play, which only know `--server` and `--port`.
+	pub fn legacy_arguments(&self) -> Result<Vec<String>> {
+		let address = match self {
+			Self::Multiplayer(address) => address,
+			Self::Singleplayer(_) => return Err(Box::new(QuickPlayError::Unsupported("world"))),
+			Self::Realms(_) => return Err(Box::new(QuickPlayError::Unsupported("Realm"))),
+		};
+
+		let (host, port) = split_address(address)?;
+
+		Ok(vec![
+			"--server".into(),
+			host.into(),
+			"--port".into(),
+			port.to_string(),
+		])
+	}
+}
+
+/// Split a server address into its host and port, which defaults to 25565.
+/// Accepts `host`, `host:port`, a bare IPv6 address, or `[address]` and `[address]:port`.
+fn split_address(address: &str) -> Result<(&str, u16)> {
+	let invalid_port = || Box::new(QuickPlayError::InvalidPort(address.to_string()));
+	if let Some(bracketed) = address.strip_prefix('[') {
+		let (host, rest) = bracketed
+			.split_once(']')
+			.ok_or_else(|| Box::new(QuickPlayError::InvalidAddress(address.to_string())))?;
+		return match rest {
+			"" => Ok((host, 25565)),
+			rest => match rest.strip_prefix(':').map(str::parse::<u16>) {
+				Some(Ok(port)) => Ok((host, port)),
+				_ => Err(invalid_port()),
+			},
+		};
+	}
+	match address.split_once(':') {
+		// More than one colon is an IPv6 address without a port.
+		Some((_, rest)) if rest.contains(':') => Ok((address, 25565)),
+		Some((host, port)) => Ok((host, port.parse().map_err(|_| invalid_port())?)),
+		None => Ok((address, 25565)),
+	}
+}
+
+/// An entry in the log Minecraft writes to `${quickPlayPath}` after a quick play attempt.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct QuickPlayLogEntry {
+	#[serde(alias = "quickPlayWorld")]
+	pub world: QuickPlayWorld,
+	#[serde(alias = "lastPlayedTime", skip_serializing_if = "Option::is_none")]
+	pub last_played_time: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub gamemode: Option<String>,
+}
+
+/// The world, server, or Realm a [QuickPlayLogEntry] is about.
+/// `world_type`: Observed values: `singleplayer`, `multiplayer`, `realms`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct QuickPlayWorld {
+	#[serde(alias = "type")]
+	pub world_type: String,
+	pub id: String,
+	pub name: String,
+}
+
+/// Read the quick play log. Returns an empty list if Minecraft hasn't written one.
+pub fn read_log(path: &Path) -> Result<Vec<QuickPlayLogEntry>> {
+	if !path.exists() {
+		return Ok(Vec::new());
+	}
+	Ok(serde_json::from_reader(File::open(path)?)?)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::split_address;
+
+	#[test]
+	fn splits_addresses() {
+		for (address, expected) in [
+			("example.com", Some(("example.com", 25565))),
+			("example.com:25566", Some(("example.com", 25566))),
+			("::1", Some(("::1", 25565))),
+			("[::1]", Some(("::1", 25565))),
+			("[2001:db8::1]:25566", Some(("2001:db8::1", 25566))),
+			("example.com:port", None),
+			("[::1]:", None),
+			("[::1", None),
+		] {
+			assert_eq!(split_address(address).ok(), expected, "{address}");
+		}
+	}
+}
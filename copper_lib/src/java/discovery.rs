@@ -0,0 +1,136 @@
+use super::JavaInstallation;
+use std::{
+	collections::HashSet,
+	env,
+	fs::{self, read_dir},
+	path::{Path, PathBuf},
+	process::Command,
+	time::UNIX_EPOCH,
+};
+use tracing::warn;
+
+/// Directories that commonly contain one JDK/JRE per sub-directory.
+const JVM_DIRECTORIES: &[&str] = &[
+	"/usr/lib/jvm",
+	"/usr/lib64/jvm",
+	"/usr/lib32/jvm",
+	"/usr/java",
+	"/usr/local/java",
+	"/opt/java",
+	"/opt/jdk",
+	"/opt/jdks",
+	"/Library/Java/JavaVirtualMachines",
+];
+
+/// Same as [JVM_DIRECTORIES], but relative to the home directory.
+const HOME_JVM_DIRECTORIES: &[&str] = &[
+	".sdkman/candidates/java",
+	".jdks",
+	".gradle/jdks",
+	".local/share/JetBrains/Toolbox/apps/jdks",
+	"Library/Java/JavaVirtualMachines",
+];
+
+/// Find every `java` executable in the usual places, with symlinks resolved and duplicates removed.
+pub fn candidates() -> Vec<PathBuf> {
+	let mut candidates = Vec::new();
+
+	if let Some(path) = env::var_os("PATH") {
+		for directory in env::split_paths(&path) {
+			candidates.push(directory.join("java"));
+		}
+	}
+	if let Some(java_home) = env::var_os("JAVA_HOME") {
+		candidates.push(PathBuf::from(java_home).join("bin/java"));
+	}
+
+	let mut directories: Vec<PathBuf> = JVM_DIRECTORIES.iter().map(PathBuf::from).collect();
+	if let Some(sdkman) = env::var_os("SDKMAN_DIR") {
+		directories.push(PathBuf::from(sdkman).join("candidates/java"));
+	}
+	if let Some(home) = dirs::home_dir() {
+		directories.extend(HOME_JVM_DIRECTORIES.iter().map(|dir| home.join(dir)));
+	}
+	for directory in directories {
+		let entries = match read_dir(&directory) {
+			Ok(entries) => entries,
+			Err(_) => continue,
+		};
+		for entry in entries.flatten() {
+			let home = entry.path();
+			candidates.push(home.join("bin/java"));
+			candidates.push(home.join("jre/bin/java"));
+			candidates.push(home.join("Contents/Home/bin/java"));
+		}
+	}
+
+	let mut seen = HashSet::new();
+	candidates
+		.into_iter()
+		.filter_map(|candidate| fs::canonicalize(candidate).ok())
+		.filter(|candidate| candidate.is_file() && seen.insert(candidate.clone()))
+		.collect()
+}
+
+/// Find out which version of Java an executable is, from its `release` file if possible, otherwise by running `java -version`.
+pub fn probe(java: &Path) -> Option<JavaInstallation> {
+	let version = read_release_file(java).or_else(|| run_version(java))?;
+	let major_version = parse_major_version(&version)?;
+
+	Some(JavaInstallation {
+		path: java.to_path_buf(),
+		version,
+		major_version,
+		modified: modified(java),
+	})
+}
+
+/// Modification time of a file in milliseconds, used to invalidate cached probes.
+pub fn modified(path: &Path) -> u128 {
+	fs::metadata(path)
+		.and_then(|metadata| metadata.modified())
+		.ok()
+		.and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+		.map_or(0, |modified| modified.as_millis())
+}
+
+/// The `release` file is at the root of the JDK, which is `bin/..`, or `jre/bin/../..` for Java 8 JREs inside a JDK.
+fn read_release_file(java: &Path) -> Option<String> {
+	let bin = java.parent()?;
+	for home in bin.ancestors().skip(1).take(2) {
+		if let Ok(release) = fs::read_to_string(home.join("release")) {
+			for line in release.lines() {
+				if let Some(version) = line.strip_prefix("JAVA_VERSION=") {
+					return Some(version.trim_matches('"').to_string());
+				}
+			}
+		}
+	}
+	None
+}
+
+/// `java -version` prints to stderr, e.g. `openjdk version "17.0.2" 2022-01-18`.
+fn run_version(java: &Path) -> Option<String> {
+	let output = match Command::new(java).arg("-version").output() {
+		Ok(output) => output,
+		Err(err) => {
+			warn!("Failed to run {}: {err}", java.display());
+			return None;
+		}
+	};
+	let stderr = String::from_utf8_lossy(&output.stderr);
+	let line = stderr.lines().find(|line| line.contains("version \""))?;
+	let version = line.split('"').nth(1)?;
+	Some(version.to_string())
+}
+
+/// `1.8.0_292` is Java 8, everything from Java 9 onwards starts with the major version (`17.0.2`, `21`).
+pub fn parse_major_version(version: &str) -> Option<i32> {
+	let mut parts = version.split(|c: char| !c.is_ascii_digit());
+	let first = parts.next()?.parse().ok()?;
+	if first == 1 {
+		parts.next()?.parse().ok()
+	} else {
+		Some(first)
+	}
+}
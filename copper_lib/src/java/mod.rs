@@ -0,0 +1,157 @@
+//! Finding a Java runtime to launch Minecraft with.
+
+pub mod discovery;
+pub mod runtime;
+
+use crate::{structs::profile::JavaVersion, Paths, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+	collections::HashMap,
+	error::Error,
+	fmt,
+	fs::File,
+	path::{Path, PathBuf},
+};
+use tracing::{info, warn};
+
+/// A Java executable, along with its version.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct JavaInstallation {
+	/// Path to the `java` executable.
+	pub path: PathBuf,
+	pub version: String,
+	pub major_version: i32,
+	/// Modification time of the executable when it was probed, in milliseconds.
+	#[serde(default)]
+	pub modified: u128,
+}
+
+#[derive(Debug)]
+pub enum JavaError {
+	NotFound {
+		required: i32,
+		found: Vec<JavaInstallation>,
+	},
+}
+impl fmt::Display for JavaError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::NotFound { required, found } => {
+				write!(f, "No Java {required} or newer found")?;
+				if found.is_empty() {
+					write!(f, ", and no other Java installations either")?;
+				} else {
+					write!(f, ". Found:")?;
+					for java in found {
+						write!(f, "\n  Java {} at {}", java.version, java.path.display())?;
+					}
+				}
+				write!(
+					f,
+					"\nInstall Java {required}, or set JAVA_HOME to an existing installation."
+				)
+			}
+		}
+	}
+}
+impl Error for JavaError {}
+
+fn cache_path(path: &Paths) -> PathBuf {
+	path.cache.join("java.json")
+}
+
+fn load_cache(path: &Path) -> HashMap<PathBuf, JavaInstallation> {
+	File::open(path)
+		.ok()
+		.and_then(|file| serde_json::from_reader(file).ok())
+		.unwrap_or_default()
+}
+
+/// Probe every Java installation on the system, unless it's in the cache and hasn't changed since.
+fn probe_all(cache: &HashMap<PathBuf, JavaInstallation>) -> Vec<JavaInstallation> {
+	let mut installations = Vec::new();
+	for java in discovery::candidates() {
+		let cached = cache
+			.get(&java)
+			.filter(|cached| cached.modified == discovery::modified(&java));
+		let installation = match cached {
+			Some(cached) => Some(cached.clone()),
+			None => discovery::probe(&java),
+		};
+		if let Some(installation) = installation {
+			installations.push(installation);
+		}
+	}
+	installations
+}
+
+/// Find all Java installations on the system. Versions are cached, and only re-probed if the executable changed.
+pub fn discover(path: &Paths) -> Result<Vec<JavaInstallation>> {
+	let cache_path = cache_path(path);
+	let installations = probe_all(&load_cache(&cache_path));
+
+	let cache: HashMap<_, _> = installations
+		.iter()
+		.map(|java| (java.path.clone(), java.clone()))
+		.collect();
+	serde_json::to_writer(File::create(cache_path)?, &cache)?;
+
+	Ok(installations)
+}
+
+/// Pick the installation for a major version of Java.
+/// An exact match is preferred, since old versions of Minecraft can break on newer Java. Otherwise the oldest newer version is used.
+pub fn select(installations: &[JavaInstallation], major_version: i32) -> Option<&JavaInstallation> {
+	installations
+		.iter()
+		.filter(|java| java.major_version >= major_version)
+		.min_by_key(|java| java.major_version)
+}
+
+/// Find a Java installation on the system that can run a major version of Java.
+pub fn find(path: &Paths, major_version: i32) -> Result<JavaInstallation> {
+	let installations = discover(path)?;
+	match select(&installations, major_version) {
+		Some(java) => {
+			info!("Using Java {} at {}", java.version, java.path.display());
+			Ok(java.clone())
+		}
+		None => Err(Box::new(JavaError::NotFound {
+			required: major_version,
+			found: installations,
+		})),
+	}
+}
+
+/// Get the Java runtime for a profile. Mojang's runtime for the profile's component is used if it's available for this platform, otherwise an installation on the system is used.
+pub async fn resolve(path: &Paths, java_version: &JavaVersion) -> Result<JavaInstallation> {
+	match runtime::install(path, &java_version.component).await {
+		Ok(java) => {
+			info!("Using Java {} ({})", java.version, java_version.component);
+			Ok(java)
+		}
+		Err(err) => {
+			warn!("{err}, looking for Java on the system instead");
+			find(path, java_version.major_version)
+		}
+	}
+}
+
+/// The Java runtime [resolve] would use for a profile, without installing anything or writing the cache.
+/// Mojang's runtime is used if it's already installed, then an installation on the system.
+/// If there's neither, it's where Mojang's runtime would be installed, assumed to be the version the profile asks for.
+pub fn locate(path: &Paths, java_version: &JavaVersion) -> JavaInstallation {
+	if let Some(java) = runtime::installed(path, &java_version.component) {
+		return java;
+	}
+	let installations = probe_all(&load_cache(&cache_path(path)));
+	match select(&installations, java_version.major_version) {
+		Some(java) => java.clone(),
+		None => JavaInstallation {
+			path: runtime::executable_path(path, &java_version.component),
+			version: java_version.major_version.to_string(),
+			major_version: java_version.major_version,
+			modified: 0,
+		},
+	}
+}
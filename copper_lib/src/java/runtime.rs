@@ -0,0 +1,176 @@
+//! Mojang's official Java runtimes, downloaded file-by-file into the store.
+//!
+//! Files are saved to the store by the sha512 of their contents, so identical files in different runtime versions (or different components) are only stored once.
+
+use super::{discovery, JavaInstallation};
+use crate::{
+	api::mojang::{fetch_java_runtimes, fetch_runtime_manifest},
+	store::{fetch_sha1, hard_link},
+	structs::java_runtime::{RuntimeEntry, RuntimeFile},
+	Paths, Result,
+};
+use std::{
+	env::consts::{ARCH, OS},
+	error::Error,
+	fmt,
+	fs::{self, create_dir_all},
+	os::unix,
+	path::{Path, PathBuf},
+};
+use tracing::info;
+
+/// Written to the root of a runtime once every file has been materialized, containing the runtime's version.
+const VERSION_FILE: &str = ".copper_version";
+
+#[derive(Debug)]
+pub enum RuntimeError {
+	UnsupportedPlatform,
+	Unavailable { component: String, platform: String },
+	MissingExecutable(PathBuf),
+}
+impl fmt::Display for RuntimeError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::UnsupportedPlatform => write!(f, "Mojang doesn't provide Java for {OS}/{ARCH}"),
+			Self::Unavailable {
+				component,
+				platform,
+			} => write!(f, "Java runtime {component} isn't available for {platform}"),
+			Self::MissingExecutable(path) => {
+				write!(
+					f,
+					"Java runtime at {} has no java executable",
+					path.display()
+				)
+			}
+		}
+	}
+}
+impl Error for RuntimeError {}
+
+/// Name of the current platform in Mojang's runtime list.
+pub fn platform() -> Option<&'static str> {
+	Some(match (OS, ARCH) {
+		("linux", "x86_64") => "linux",
+		("linux", "x86") => "linux-i386",
+		("macos", "x86_64") => "mac-os",
+		("macos", "aarch64") => "mac-os-arm64",
+		("windows", "x86_64") => "windows-x64",
+		("windows", "x86") => "windows-x86",
+		("windows", "aarch64") => "windows-arm64",
+		_ => return None,
+	})
+}
+
+/// Find the newest release of a component for the current platform.
+async fn find_entry(component: &str) -> Result<RuntimeEntry> {
+	let platform = platform().ok_or(RuntimeError::UnsupportedPlatform)?;
+
+	let runtimes = fetch_java_runtimes().await?;
+	runtimes
+		.get(platform)
+		.and_then(|components| components.get(component))
+		.and_then(|entries| entries.first())
+		.cloned()
+		.ok_or_else(|| {
+			Box::new(RuntimeError::Unavailable {
+				component: component.to_string(),
+				platform: platform.to_string(),
+			})
+			.into()
+		})
+}
+
+/// Where the `java` executable is inside a runtime. macOS runtimes are app bundles.
+fn executable(root: &Path) -> Option<PathBuf> {
+	[
+		root.join("bin/java"),
+		root.join("jre.bundle/Contents/Home/bin/java"),
+	]
+	.into_iter()
+	.find(|path| path.is_file())
+}
+
+/// Where the `java` executable of a runtime component is once it's installed.
+/// macOS runtimes can't be told apart before they're installed, so this is only a guess there.
+pub fn executable_path(path: &Paths, component: &str) -> PathBuf {
+	let root = path.runtimes.join(component);
+	executable(&root).unwrap_or_else(|| root.join("bin/java"))
+}
+
+/// A runtime component that has already been installed, whichever version it is.
+pub fn installed(path: &Paths, component: &str) -> Option<JavaInstallation> {
+	let root = path.runtimes.join(component);
+	let version = fs::read_to_string(root.join(VERSION_FILE)).ok()?;
+	let java = executable(&root)?;
+	Some(JavaInstallation {
+		modified: discovery::modified(&java),
+		path: java,
+		major_version: discovery::parse_major_version(&version).unwrap_or_default(),
+		version,
+	})
+}
+
+/// Download a runtime component into the store and materialize it, unless the newest version already is.
+pub async fn install(path: &Paths, component: &str) -> Result<JavaInstallation> {
+	let entry = find_entry(component).await?;
+	let root = path.runtimes.join(component);
+	let version_file = root.join(VERSION_FILE);
+
+	if fs::read_to_string(&version_file).ok().as_deref() != Some(entry.version.name.as_str()) {
+		info!(
+			"Installing Java runtime {component} {}...",
+			entry.version.name
+		);
+		materialize(&root, &entry).await?;
+		fs::write(&version_file, &entry.version.name)?;
+	}
+
+	let java = executable(&root).ok_or(RuntimeError::MissingExecutable(root))?;
+	let major_version = discovery::parse_major_version(&entry.version.name).unwrap_or_default();
+	Ok(JavaInstallation {
+		modified: discovery::modified(&java),
+		path: java,
+		version: entry.version.name,
+		major_version,
+	})
+}
+
+/// Hardlink every file of a runtime from the store, and recreate its directories and symlinks.
+async fn materialize(root: &Path, entry: &RuntimeEntry) -> Result<()> {
+	let manifest = fetch_runtime_manifest(&entry.manifest).await?;
+	if root.exists() {
+		fs::remove_dir_all(root)?;
+	}
+	create_dir_all(root)?;
+
+	let mut handles = Vec::new();
+	let mut links = Vec::new();
+	for (name, file) in manifest.files {
+		let path = root.join(&name);
+		match file {
+			RuntimeFile::Directory => create_dir_all(&path)?,
+			RuntimeFile::File {
+				downloads,
+				executable,
+			} => {
+				handles.push(tokio::spawn(async move {
+					let store = fetch_sha1(downloads.raw.sha1, downloads.raw.url).await?;
+					hard_link(&store, &path, executable)
+				}));
+			}
+			RuntimeFile::Link { target } => links.push((path, target)),
+		}
+	}
+	for handle in handles {
+		handle.await??;
+	}
+
+	// Links are made last, since their parent directories might not exist before.
+	for (path, target) in links {
+		create_dir_all(path.parent().unwrap())?;
+		unix::fs::symlink(target, path)?;
+	}
+
+	Ok(())
+}
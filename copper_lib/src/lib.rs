@@ -6,6 +6,9 @@ pub mod api;
 mod directories;
 pub use directories::*;
 pub mod instance;
+pub mod java;
+pub mod mappings;
+pub mod resolver;
 pub mod store;
 pub mod structs;
 
@@ -0,0 +1,391 @@
+//! Parsing ProGuard mapping files, and using them to deobfuscate stack traces and crash reports.
+//!
+//! Mojang publishes mappings for versions since 1.14.4 in this format:
+//! ```text
+//! net.minecraft.client.Minecraft -> emh:
+//!     java.lang.String name -> a
+//!     123:145:void tick() -> b
+//! ```
+
+use crate::{store::fetch_sha1, structs::profile::Download, Result};
+use std::{collections::HashMap, fs, path::Path};
+
+/// A method, along with the source lines it covers if they're known.
+#[derive(Clone, Debug)]
+pub struct MethodMapping {
+	pub name: String,
+	pub lines: Option<(u32, u32)>,
+}
+
+/// A class and its members, keyed by their obfuscated names.
+#[derive(Clone, Debug, Default)]
+pub struct ClassMapping {
+	pub name: String,
+	pub fields: HashMap<String, String>,
+	/// Several methods can share an obfuscated name if they have different parameters.
+	pub methods: HashMap<String, Vec<MethodMapping>>,
+}
+
+/// Mappings for every class, keyed by the obfuscated class name.
+#[derive(Clone, Debug, Default)]
+pub struct Mappings {
+	pub classes: HashMap<String, ClassMapping>,
+}
+
+/// A field or method line, before it's added to its class.
+struct Member {
+	name: String,
+	is_method: bool,
+	lines: Option<(u32, u32)>,
+}
+
+/// Parse `[from:to:]type name(params)[:from:to]`.
+fn parse_member(member: &str) -> Option<Member> {
+	let mut parts = member.splitn(3, ':');
+	let (lines, signature) = match (parts.next(), parts.next(), parts.next()) {
+		(Some(from), Some(to), Some(rest)) if from.chars().all(|c| c.is_ascii_digit()) => {
+			(Some((from.parse().ok()?, to.parse().ok()?)), rest)
+		}
+		_ => (None, member),
+	};
+
+	let name = signature.split_whitespace().nth(1)?;
+	Some(match name.split_once('(') {
+		Some((name, _)) => Member {
+			name: name.to_string(),
+			is_method: true,
+			lines,
+		},
+		None => Member {
+			name: name.to_string(),
+			is_method: false,
+			lines: None,
+		},
+	})
+}
+
+impl Mappings {
+	pub fn parse(text: &str) -> Self {
+		let mut mappings = Self::default();
+		let mut current: Option<&mut ClassMapping> = None;
+
+		for line in text.lines() {
+			if line.trim_start().starts_with('#') || line.trim().is_empty() {
+				continue;
+			}
+			let (left, obfuscated) = match line.rsplit_once(" -> ") {
+				Some(split) => split,
+				None => continue,
+			};
+
+			if !line.starts_with(char::is_whitespace) {
+				let obfuscated = obfuscated.trim_end_matches(':').to_string();
+				let class = mappings.classes.entry(obfuscated).or_default();
+				class.name = left.to_string();
+				current = Some(class);
+				continue;
+			}
+
+			let class = match &mut current {
+				Some(class) => class,
+				None => continue,
+			};
+			let member = match parse_member(left.trim()) {
+				Some(member) => member,
+				None => continue,
+			};
+			if member.is_method {
+				class
+					.methods
+					.entry(obfuscated.to_string())
+					.or_default()
+					.push(MethodMapping {
+						name: member.name,
+						lines: member.lines,
+					});
+			} else {
+				class.fields.insert(obfuscated.to_string(), member.name);
+			}
+		}
+
+		mappings
+	}
+
+	pub fn load(path: &Path) -> Result<Self> {
+		Ok(Self::parse(&fs::read_to_string(path)?))
+	}
+
+	/// Fetch mappings (such as [Downloads::client_mappings](crate::structs::profile::Downloads::client_mappings)) through the store.
+	pub async fn fetch(download: &Download) -> Result<Self> {
+		let store = fetch_sha1(download.sha1.clone(), download.url.clone()).await?;
+		Self::load(&store.path)
+	}
+
+	/// The readable name of a class, including inner classes like `a$b`.
+	pub fn class(&self, obfuscated: &str) -> Option<&str> {
+		self.classes
+			.get(obfuscated)
+			.map(|class| class.name.as_str())
+	}
+
+	/// The readable name of a method. If several methods share the obfuscated name, the line number from the stack trace picks one.
+	pub fn method(&self, class: &str, obfuscated: &str, line: Option<u32>) -> Option<String> {
+		let methods = self.classes.get(class)?.methods.get(obfuscated)?;
+		if let Some(line) = line {
+			let found = methods.iter().find(|method| {
+				method
+					.lines
+					.is_some_and(|(from, to)| (from..=to).contains(&line))
+			});
+			if let Some(method) = found {
+				return Some(method.name.clone());
+			}
+		}
+
+		let mut names: Vec<&str> = methods.iter().map(|method| method.name.as_str()).collect();
+		names.dedup();
+		Some(names.join("|"))
+	}
+
+	pub fn field(&self, class: &str, obfuscated: &str) -> Option<&str> {
+		self.classes
+			.get(class)?
+			.fields
+			.get(obfuscated)
+			.map(String::as_str)
+	}
+
+	/// Deobfuscate a stack trace or crash report.
+	pub fn deobfuscate(&self, text: &str) -> String {
+		text.lines()
+			.map(|line| self.deobfuscate_line(line))
+			.collect::<Vec<_>>()
+			.join("\n")
+	}
+
+	/// Find which version of Minecraft a crash report is from, from its `Minecraft Version` line.
+	pub fn report_version(report: &str) -> Option<&str> {
+		report.lines().find_map(|line| {
+			let line = line.trim();
+			line.strip_prefix("Minecraft Version ID: ")
+				.or_else(|| line.strip_prefix("Minecraft Version: "))
+				.map(str::trim)
+		})
+	}
+
+	/// Only stack frames (`at a.b(SourceFile:12)`) and exception headers are rewritten, since obfuscated names are short
+	/// enough to appear in ordinary text.
+	pub fn deobfuscate_line(&self, line: &str) -> String {
+		let trimmed = line.trim_start();
+		let indent = &line[..line.len() - trimmed.len()];
+
+		let deobfuscated = match trimmed.strip_prefix("at ") {
+			Some(frame) => self
+				.deobfuscate_frame(frame)
+				.map(|frame| format!("at {frame}")),
+			None => self.deobfuscate_header(trimmed),
+		};
+		match deobfuscated {
+			Some(deobfuscated) => format!("{indent}{deobfuscated}"),
+			None => line.to_string(),
+		}
+	}
+
+	/// `Caused by: a: message`, `Exception in thread "main" a: message`, or `net.minecraft.a: message`.
+	/// Without one of the prefixes, the exception has to be fully qualified, so lines like `Description: ...` are left alone.
+	fn deobfuscate_header(&self, line: &str) -> Option<String> {
+		let (prefix, rest) = if let Some(rest) = line.strip_prefix("Caused by: ") {
+			(line.len() - rest.len(), rest)
+		} else if let Some(thread) = line.strip_prefix("Exception in thread \"") {
+			let (_, rest) = thread.split_once("\" ")?;
+			(line.len() - rest.len(), rest)
+		} else {
+			(0, line)
+		};
+		let (class, message) = rest.split_at(rest.find(": ").unwrap_or(rest.len()));
+		if !is_class_name(class) || (prefix == 0 && !class.contains('.')) {
+			return None;
+		}
+		// Exceptions like NullPointerException aren't obfuscated, but their messages can still refer to obfuscated members.
+		let name = self.class(class).unwrap_or(class);
+		Some(format!(
+			"{}{name}{}",
+			&line[..prefix],
+			self.deobfuscate_message(message)
+		))
+	}
+
+	/// Rewrite quoted `class.member` references, like the ones in helpful NullPointerException messages
+	/// (`Cannot invoke "emh.b()" because "emh.c" is null`).
+	fn deobfuscate_message(&self, message: &str) -> String {
+		let mut parts: Vec<String> = message.split('"').map(String::from).collect();
+		for quoted in parts.iter_mut().skip(1).step_by(2) {
+			if let Some(reference) = self.deobfuscate_reference(quoted) {
+				*quoted = reference;
+			}
+		}
+		parts.join("\"")
+	}
+
+	/// `emh.c` -> `net.minecraft.client.Minecraft.player`, and `emh.b()` -> `net.minecraft.client.Minecraft.tick()`.
+	fn deobfuscate_reference(&self, reference: &str) -> Option<String> {
+		let (path, call) = reference.split_at(reference.find('(').unwrap_or(reference.len()));
+		let (class, member) = path.rsplit_once('.')?;
+		if !is_class_name(class) || !is_identifier(member) {
+			return None;
+		}
+		let class_name = self.class(class)?;
+		let member_name = match call.is_empty() {
+			true => self.field(class, member)?.to_string(),
+			false => self.method(class, member, None)?,
+		};
+		Some(format!("{class_name}.{member_name}{call}"))
+	}
+
+	/// `a.b(SourceFile:12)` -> `net.minecraft.Foo.bar(SourceFile:12)`, keeping anything after the location
+	/// (like Forge's `~[client.jar:?]`).
+	fn deobfuscate_frame(&self, frame: &str) -> Option<String> {
+		let open = frame.find('(')?;
+		let close = open + frame[open..].find(')')?;
+		let (method_path, location, rest) =
+			(&frame[..open], &frame[open..=close], &frame[close + 1..]);
+		let (class, method) = method_path.rsplit_once('.')?;
+		if !is_class_name(class) || !(is_identifier(method) || method.starts_with('<')) {
+			return None;
+		}
+		let class_name = self.class(class)?;
+
+		let line = location[1..location.len() - 1]
+			.rsplit(':')
+			.next()
+			.and_then(|line| line.parse().ok());
+		let method_name = self
+			.method(class, method, line)
+			.unwrap_or_else(|| method.to_string());
+		Some(format!("{class_name}.{method_name}{location}{rest}"))
+	}
+}
+
+/// A Java identifier, such as a class's simple name or a member's name.
+fn is_identifier(name: &str) -> bool {
+	let mut chars = name.chars();
+	chars
+		.next()
+		.is_some_and(|first| first.is_alphabetic() || first == '_' || first == '$')
+		&& chars.all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+}
+
+/// A class name, optionally in a package, like `emh`, `emh$a` or `net.minecraft.client.Minecraft`.
+fn is_class_name(name: &str) -> bool {
+	name.split('.').all(is_identifier)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const MAPPINGS: &str = "\
+# compiler: R8
+net.minecraft.client.Minecraft -> emh:
+    net.minecraft.client.player.LocalPlayer player -> c
+    java.lang.String NAME -> a
+    10:20:void tick() -> b
+    30:35:void render(float) -> b
+    40:41:void <init>() -> <init>
+net.minecraft.client.Minecraft$Inner -> emh$a:
+    int count -> a
+net.minecraft.ReportedException -> y:
+";
+
+	fn mappings() -> Mappings {
+		Mappings::parse(MAPPINGS)
+	}
+
+	#[test]
+	fn parses_mappings() {
+		let mappings = mappings();
+		assert_eq!(mappings.classes.len(), 3);
+		assert_eq!(
+			mappings.class("emh"),
+			Some("net.minecraft.client.Minecraft")
+		);
+		assert_eq!(
+			mappings.class("emh$a"),
+			Some("net.minecraft.client.Minecraft$Inner")
+		);
+		assert_eq!(mappings.field("emh", "c"), Some("player"));
+		assert_eq!(mappings.field("emh$a", "a"), Some("count"));
+		assert_eq!(mappings.field("emh", "b"), None);
+
+		// Line numbers pick between methods that share an obfuscated name.
+		assert_eq!(
+			mappings.method("emh", "b", Some(12)).as_deref(),
+			Some("tick")
+		);
+		assert_eq!(
+			mappings.method("emh", "b", Some(31)).as_deref(),
+			Some("render")
+		);
+		assert_eq!(
+			mappings.method("emh", "b", None).as_deref(),
+			Some("tick|render")
+		);
+		assert_eq!(mappings.method("emh", "z", None), None);
+	}
+
+	#[test]
+	fn deobfuscates_stack_traces() {
+		let mappings = mappings();
+		for (line, expected) in [
+			(
+				"\tat emh.b(SourceFile:31)",
+				"\tat net.minecraft.client.Minecraft.render(SourceFile:31)",
+			),
+			(
+				"\tat emh.<init>(SourceFile:40) ~[client-1.20.1.jar:?] {}",
+				"\tat net.minecraft.client.Minecraft.<init>(SourceFile:40) ~[client-1.20.1.jar:?] {}",
+			),
+			(
+				"\tat emh$a.run(SourceFile:5)",
+				"\tat net.minecraft.client.Minecraft$Inner.run(SourceFile:5)",
+			),
+			(
+				"Caused by: y: Ticking entity",
+				"Caused by: net.minecraft.ReportedException: Ticking entity",
+			),
+			(
+				"Exception in thread \"Render thread\" y: Ticking entity",
+				"Exception in thread \"Render thread\" net.minecraft.ReportedException: Ticking entity",
+			),
+			(
+				"java.lang.NullPointerException: Cannot invoke \"emh.b()\" because \"emh.c\" is null",
+				"java.lang.NullPointerException: Cannot invoke \"net.minecraft.client.Minecraft.tick|render()\" because \"net.minecraft.client.Minecraft.player\" is null",
+			),
+		] {
+			assert_eq!(mappings.deobfuscate_line(line), expected);
+		}
+	}
+
+	#[test]
+	fn leaves_other_text_alone() {
+		let mappings = mappings();
+		for line in [
+			// Not fully qualified, and not after `Caused by: `.
+			"y: not an exception",
+			"Description: Ticking entity \"emh.c\"",
+			"emh",
+			"\tat java.lang.Thread.run(Thread.java:833)",
+			"\tat emh.b",
+			"Details: a.b, c.d: \"not.quoted.right",
+		] {
+			assert_eq!(mappings.deobfuscate_line(line), line);
+		}
+	}
+
+	#[test]
+	fn finds_report_versions() {
+		let report = "---- Minecraft Crash Report ----\n\n-- System Details --\n\tMinecraft Version: 1.20.1\n";
+		assert_eq!(Mappings::report_version(report), Some("1.20.1"));
+		assert_eq!(Mappings::report_version("nothing here"), None);
+	}
+}
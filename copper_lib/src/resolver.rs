@@ -0,0 +1,787 @@
+//! Resolving mod dependencies, independent of where mods come from.
+//!
+//! Providers (like Modrinth) list the versions of a project that work with an instance, and what each version depends on.
+//! The resolver picks one version of every project that's needed, so that every dependency is satisfied and nothing
+//! incompatible is installed together, or explains why that isn't possible.
+
+use crate::Result;
+use std::{
+	collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+	error::Error,
+	fmt,
+	future::Future,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DependencyKind {
+	Required,
+	Optional,
+	Incompatible,
+	/// Bundled inside the dependent, so it doesn't need installing.
+	Embedded,
+}
+
+/// A dependency on a project, or on a specific version of it if `version` is set.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Dependency {
+	pub project: String,
+	pub version: Option<String>,
+	pub kind: DependencyKind,
+}
+
+/// A version of a project that could be installed, along with whatever the provider needs to install it.
+#[derive(Clone, Debug)]
+pub struct Candidate<T> {
+	pub project: String,
+	/// The version's ID.
+	pub version: String,
+	/// Project name to show in messages.
+	pub name: String,
+	pub version_number: String,
+	pub dependencies: Vec<Dependency>,
+	pub data: T,
+}
+
+impl<T> Candidate<T> {
+	/// Whether this is the version a constraint asks for, by ID or version number.
+	fn is(&self, version: &str) -> bool {
+		self.version == version || self.version_number == version
+	}
+}
+
+impl<T> fmt::Display for Candidate<T> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{} {}", self.name, self.version_number)
+	}
+}
+
+/// Somewhere mods come from.
+pub trait Provider {
+	type Data: Clone;
+
+	/// Versions of a project that work with the instance (its game version and loader), most preferred first.
+	fn versions(
+		&self,
+		project: &str,
+	) -> impl Future<Output = Result<Vec<Candidate<Self::Data>>>> + Send;
+}
+
+/// An optional dependency that isn't installed, which the user could add.
+#[derive(Clone, Debug)]
+pub struct Suggestion {
+	pub project: String,
+	pub name: String,
+	/// Who suggests it, like `sodium 0.5.3`.
+	pub by: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct Resolution<T> {
+	/// Versions to install, including ones that replace installed versions. In project order.
+	pub install: Vec<Candidate<T>>,
+	pub suggestions: Vec<Suggestion>,
+}
+
+#[derive(Clone, Debug)]
+pub enum ResolveError {
+	/// No version of a project works with the instance and meets every constraint on it.
+	Unsatisfiable {
+		project: String,
+		constraints: Vec<String>,
+	},
+	/// An installed version doesn't meet a constraint, so it has to be updated or removed first.
+	Installed {
+		installed: String,
+		constraints: Vec<String>,
+	},
+	Incompatible {
+		declared_by: String,
+		with: String,
+	},
+}
+impl fmt::Display for ResolveError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Unsatisfiable {
+				project,
+				constraints,
+			} => {
+				write!(f, "No version of {project} works with this instance")?;
+				if !constraints.is_empty() {
+					write!(f, " while meeting every constraint on it:")?;
+					for constraint in constraints {
+						write!(f, "\n  - {constraint}")?;
+					}
+				}
+				Ok(())
+			}
+			Self::Installed {
+				installed,
+				constraints,
+			} => {
+				write!(
+					f,
+					"{installed} is installed, which doesn't meet every constraint on it:"
+				)?;
+				for constraint in constraints {
+					write!(f, "\n  - {constraint}")?;
+				}
+				write!(f, "\nUpdate or remove it first")
+			}
+			Self::Incompatible { declared_by, with } => {
+				write!(f, "{declared_by} is incompatible with {with}")
+			}
+		}
+	}
+}
+impl Error for ResolveError {}
+
+/// How many sets of rejected versions [resolve] tries before giving up on a conflict.
+const MAX_ATTEMPTS: usize = 1000;
+
+/// What one pass of [resolve] found.
+pub enum Step<T> {
+	Resolved(Resolution<T>),
+	/// Projects whose versions are needed to continue.
+	Fetch(Vec<String>),
+}
+
+/// A constraint on which version of a project can be picked, and where it came from.
+/// Constraints from picked versions keep their project and version as a `source`, so they can be blamed for conflicts.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Constraint {
+	/// Asked for directly, any version or a specific one.
+	Requested(Option<String>),
+	/// Any version, or a specific one.
+	Requires {
+		by: String,
+		version: Option<String>,
+		source: Option<(String, String)>,
+	},
+	/// Anything but this version.
+	Excludes {
+		by: String,
+		version: String,
+		source: Option<(String, String)>,
+	},
+}
+
+impl Constraint {
+	fn allows<T>(&self, candidate: &Candidate<T>) -> bool {
+		match self {
+			Self::Requested(version) | Self::Requires { version, .. } => {
+				version.as_ref().is_none_or(|version| candidate.is(version))
+			}
+			Self::Excludes { version, .. } => !candidate.is(version),
+		}
+	}
+
+	fn describe(&self) -> String {
+		match self {
+			Self::Requested(None) => "You asked for it".into(),
+			Self::Requested(Some(version)) => format!("You asked for version {version}"),
+			Self::Requires {
+				by, version: None, ..
+			} => format!("{by} needs it"),
+			Self::Requires {
+				by,
+				version: Some(version),
+				..
+			} => format!("{by} needs version {version}"),
+			Self::Excludes { by, version, .. } => {
+				format!("{by} is incompatible with version {version}")
+			}
+		}
+	}
+
+	fn source(&self) -> Option<&(String, String)> {
+		match self {
+			Self::Requested(_) => None,
+			Self::Requires { source, .. } | Self::Excludes { source, .. } => source.as_ref(),
+		}
+	}
+}
+
+fn describe_all(constraints: &BTreeSet<Constraint>) -> Vec<String> {
+	constraints.iter().map(Constraint::describe).collect()
+}
+
+/// A project's name, if any of its versions have been fetched.
+fn display_name<T>(available: &HashMap<String, Vec<Candidate<T>>>, project: &str) -> String {
+	available
+		.get(project)
+		.and_then(|versions| versions.first())
+		.map(|candidate| candidate.name.clone())
+		.unwrap_or(project.to_string())
+}
+
+/// Constraints on each project from the requests and the versions picked so far.
+/// Installed versions only constrain projects that are being picked, so installing one mod doesn't pull in another's dependencies.
+fn derive_constraints<T>(
+	requests: &[Dependency],
+	chosen: &BTreeMap<String, &Candidate<T>>,
+	fixed: &BTreeMap<&str, &Candidate<T>>,
+) -> BTreeMap<String, BTreeSet<Constraint>> {
+	let mut constraints: BTreeMap<String, BTreeSet<Constraint>> = BTreeMap::new();
+	for request in requests {
+		constraints
+			.entry(request.project.clone())
+			.or_default()
+			.insert(Constraint::Requested(request.version.clone()));
+	}
+	let picking = |project: &str| {
+		chosen.contains_key(project) || requests.iter().any(|request| request.project == project)
+	};
+
+	let dependents = chosen.values().copied().chain(
+		fixed
+			.values()
+			.copied()
+			.filter(|candidate| !chosen.contains_key(&candidate.project)),
+	);
+	for dependent in dependents {
+		let is_fixed = fixed.contains_key(dependent.project.as_str());
+		let source = (!is_fixed).then(|| (dependent.project.clone(), dependent.version.clone()));
+		for dependency in &dependent.dependencies {
+			let constraint = match (dependency.kind, &dependency.version) {
+				(DependencyKind::Required, version)
+					if !is_fixed || picking(&dependency.project) =>
+				{
+					Constraint::Requires {
+						by: dependent.to_string(),
+						version: version.clone(),
+						source: source.clone(),
+					}
+				}
+				(DependencyKind::Incompatible, Some(version))
+					if picking(&dependency.project)
+						|| fixed.contains_key(dependency.project.as_str()) =>
+				{
+					Constraint::Excludes {
+						by: dependent.to_string(),
+						version: version.clone(),
+						source: source.clone(),
+					}
+				}
+				_ => continue,
+			};
+			constraints
+				.entry(dependency.project.clone())
+				.or_default()
+				.insert(constraint);
+		}
+	}
+	constraints
+}
+
+enum Picked<'a, T> {
+	Settled(BTreeMap<String, &'a Candidate<T>>),
+	Fetch(Vec<String>),
+	/// A conflict, and the picked versions (project and version) that caused it.
+	Conflict(ResolveError, Vec<(String, String)>),
+}
+
+fn blame(constraints: &BTreeSet<Constraint>) -> Vec<(String, String)> {
+	constraints
+		.iter()
+		.filter_map(Constraint::source)
+		.cloned()
+		.collect()
+}
+
+/// Pick versions by preference, adding their dependencies' constraints, until the picks settle.
+fn pick<'a, T>(
+	available: &'a HashMap<String, Vec<Candidate<T>>>,
+	requests: &[Dependency],
+	fixed: &BTreeMap<&str, &'a Candidate<T>>,
+	rejected: &BTreeSet<(String, String)>,
+) -> Picked<'a, T> {
+	let mut chosen: BTreeMap<String, &Candidate<T>> = BTreeMap::new();
+	let mut seen = HashSet::new();
+	loop {
+		let constraints = derive_constraints(requests, &chosen, fixed);
+		let mut picked: BTreeMap<String, &Candidate<T>> = BTreeMap::new();
+		let mut missing = Vec::new();
+		for (project, project_constraints) in &constraints {
+			let allowed = |candidate: &&Candidate<T>| {
+				project_constraints
+					.iter()
+					.all(|constraint| constraint.allows(*candidate))
+			};
+			if let Some(installed) = fixed.get(project.as_str()) {
+				if !allowed(installed) {
+					let error = ResolveError::Installed {
+						installed: installed.to_string(),
+						constraints: describe_all(project_constraints),
+					};
+					return Picked::Conflict(error, blame(project_constraints));
+				}
+				picked.insert(project.clone(), installed);
+				continue;
+			}
+			let Some(versions) = available.get(project) else {
+				missing.push(project.clone());
+				continue;
+			};
+			let candidate = versions.iter().filter(allowed).find(|candidate| {
+				!rejected.contains(&(candidate.project.clone(), candidate.version.clone()))
+			});
+			match candidate {
+				Some(candidate) => {
+					picked.insert(project.clone(), candidate);
+				}
+				None => {
+					let error = ResolveError::Unsatisfiable {
+						project: display_name(available, project),
+						constraints: describe_all(project_constraints),
+					};
+					return Picked::Conflict(error, blame(project_constraints));
+				}
+			}
+		}
+		if !missing.is_empty() {
+			return Picked::Fetch(missing);
+		}
+
+		// Picking a version can change which versions its dependencies need, so this repeats until the picks settle.
+		let versions: BTreeMap<String, String> = picked
+			.iter()
+			.map(|(project, candidate)| (project.clone(), candidate.version.clone()))
+			.collect();
+		let settled = picked.len() == chosen.len()
+			&& picked.iter().all(|(project, candidate)| {
+				chosen
+					.get(project)
+					.is_some_and(|chosen| chosen.version == candidate.version)
+			});
+		chosen = picked;
+		if settled {
+			return Picked::Settled(chosen);
+		}
+		if !seen.insert(versions) {
+			// The picks are going round in circles, which only happens when dependencies contradict each other.
+			let project = &requests[0].project;
+			let error = ResolveError::Unsatisfiable {
+				project: display_name(available, project),
+				constraints: describe_all(&constraints[project]),
+			};
+			return Picked::Conflict(error, constraints.values().flat_map(blame).collect());
+		}
+	}
+}
+
+/// Check that nothing picked or installed is incompatible with anything else, returning all of them.
+#[allow(clippy::type_complexity)]
+fn check_incompatible<'a, T>(
+	chosen: &BTreeMap<String, &'a Candidate<T>>,
+	fixed: &BTreeMap<&'a str, &'a Candidate<T>>,
+) -> std::result::Result<BTreeMap<String, &'a Candidate<T>>, (ResolveError, Vec<(String, String)>)>
+{
+	let mut everything: BTreeMap<String, &Candidate<T>> = fixed
+		.iter()
+		.map(|(project, candidate)| (project.to_string(), *candidate))
+		.collect();
+	everything.extend(
+		chosen
+			.iter()
+			.map(|(project, candidate)| (project.clone(), *candidate)),
+	);
+	for candidate in everything.values() {
+		for dependency in &candidate.dependencies {
+			if dependency.kind != DependencyKind::Incompatible {
+				continue;
+			}
+			let Some(other) = everything.get(&dependency.project) else {
+				continue;
+			};
+			if dependency
+				.version
+				.as_ref()
+				.is_none_or(|version| other.is(version))
+			{
+				let error = ResolveError::Incompatible {
+					declared_by: candidate.to_string(),
+					with: other.to_string(),
+				};
+				// Other versions of either one might not be incompatible.
+				let blame = [candidate, other]
+					.into_iter()
+					.filter(|candidate| !fixed.contains_key(candidate.project.as_str()))
+					.map(|candidate| (candidate.project.clone(), candidate.version.clone()))
+					.collect();
+				return Err((error, blame));
+			}
+		}
+	}
+	Ok(everything)
+}
+
+/// Resolve requested projects against the versions fetched so far, keeping installed versions where possible.
+///
+/// Versions are picked by preference, and picking adds its dependencies' constraints, until nothing changes. Projects that
+/// haven't been fetched yet are returned as [Step::Fetch], after which this should be called again. See [resolve_with].
+/// The result only depends on its inputs, so it can be tested without a provider.
+pub fn resolve<T: Clone>(
+	available: &HashMap<String, Vec<Candidate<T>>>,
+	requests: &[Dependency],
+	installed: &[Candidate<T>],
+) -> std::result::Result<Step<T>, ResolveError> {
+	let requested: BTreeSet<&str> = requests
+		.iter()
+		.map(|request| request.project.as_str())
+		.collect();
+	// Installed versions are kept unless they're requested again, which reinstalls (or updates) them.
+	let fixed: BTreeMap<&str, &Candidate<T>> = installed
+		.iter()
+		.filter(|candidate| !requested.contains(candidate.project.as_str()))
+		.map(|candidate| (candidate.project.as_str(), candidate))
+		.collect();
+
+	// When there's a conflict, each of the picked versions that caused it is rejected in turn and everything is picked again,
+	// so an older version of a dependent can be used if its constraints work out. If nothing helps, the first conflict is reported.
+	let mut queue: Vec<BTreeSet<(String, String)>> = vec![BTreeSet::new()];
+	let mut tried = HashSet::new();
+	let mut first_error = None;
+	let (chosen, everything) = loop {
+		let rejected = match queue.pop() {
+			Some(rejected) if tried.len() < MAX_ATTEMPTS => rejected,
+			// The first attempt always happens, so there's been a conflict by now.
+			_ => return Err(first_error.unwrap()),
+		};
+		let (error, blame) = match pick(available, requests, &fixed, &rejected) {
+			Picked::Fetch(missing) => return Ok(Step::Fetch(missing)),
+			Picked::Settled(chosen) => match check_incompatible(&chosen, &fixed) {
+				Ok(everything) => break (chosen, everything),
+				Err(conflict) => conflict,
+			},
+			Picked::Conflict(error, blame) => (error, blame),
+		};
+		first_error.get_or_insert(error);
+		// The first version to blame is tried first, like it's the most likely culprit.
+		for source in blame.into_iter().rev() {
+			let mut next = rejected.clone();
+			if next.insert(source) && tried.insert(next.clone()) {
+				queue.push(next);
+			}
+		}
+	};
+
+	let mut suggestions: Vec<Suggestion> = Vec::new();
+	for candidate in chosen.values() {
+		for dependency in &candidate.dependencies {
+			let suggested = dependency.kind == DependencyKind::Optional
+				&& !everything.contains_key(&dependency.project)
+				&& !suggestions
+					.iter()
+					.any(|suggestion| suggestion.project == dependency.project);
+			if suggested {
+				suggestions.push(Suggestion {
+					project: dependency.project.clone(),
+					name: display_name(available, &dependency.project),
+					by: candidate.to_string(),
+				});
+			}
+		}
+	}
+
+	let install = chosen
+		.into_iter()
+		.filter(|(project, candidate)| {
+			fixed
+				.get(project.as_str())
+				.is_none_or(|installed| installed.version != candidate.version)
+		})
+		.map(|(_, candidate)| candidate.clone())
+		.collect();
+	Ok(Step::Resolved(Resolution {
+		install,
+		suggestions,
+	}))
+}
+
+/// Resolve requested projects, fetching versions from a provider as they're needed. See [resolve].
+pub async fn resolve_with<P: Provider>(
+	provider: &P,
+	requests: &[Dependency],
+	installed: &[Candidate<P::Data>],
+) -> Result<Resolution<P::Data>> {
+	let mut available = HashMap::new();
+	loop {
+		match resolve(&available, requests, installed)? {
+			Step::Resolved(resolution) => return Ok(resolution),
+			Step::Fetch(projects) => {
+				for project in projects {
+					let versions = provider.versions(&project).await?;
+					available.insert(project, versions);
+				}
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use DependencyKind::*;
+
+	/// A version of project `project`, with dependencies written as `(project, version, kind)`.
+	fn version(
+		project: &str,
+		version: &str,
+		dependencies: &[(&str, Option<&str>, DependencyKind)],
+	) -> Candidate<()> {
+		Candidate {
+			project: project.into(),
+			version: format!("{project}-{version}"),
+			name: project.into(),
+			version_number: version.into(),
+			dependencies: dependencies
+				.iter()
+				.map(|(project, version, kind)| Dependency {
+					project: project.to_string(),
+					version: version.map(String::from),
+					kind: *kind,
+				})
+				.collect(),
+			data: (),
+		}
+	}
+
+	fn request(project: &str, version: Option<&str>) -> Dependency {
+		Dependency {
+			project: project.into(),
+			version: version.map(String::from),
+			kind: Required,
+		}
+	}
+
+	/// What a resolution should be: the versions installed and projects suggested, or the kind of error.
+	enum Expected {
+		Resolved(&'static [&'static str], &'static [&'static str]),
+		Unsatisfiable,
+		Installed,
+		Incompatible,
+	}
+
+	struct Case {
+		name: &'static str,
+		available: Vec<Candidate<()>>,
+		installed: Vec<Candidate<()>>,
+		requests: Vec<Dependency>,
+		expected: Expected,
+	}
+
+	#[test]
+	fn resolves() {
+		let cases = [
+			Case {
+				name: "required dependencies are pulled in",
+				available: vec![
+					version("sodium", "0.5.3", &[("fabric-api", None, Required)]),
+					version("fabric-api", "0.90.0", &[]),
+					version("fabric-api", "0.89.0", &[]),
+				],
+				installed: vec![],
+				requests: vec![request("sodium", None)],
+				expected: Expected::Resolved(&["fabric-api 0.90.0", "sodium 0.5.3"], &[]),
+			},
+			Case {
+				name: "optional dependencies are suggested, not installed",
+				available: vec![
+					version("sodium", "0.5.3", &[("iris", None, Optional)]),
+					version("iris", "1.6.4", &[]),
+				],
+				installed: vec![],
+				requests: vec![request("sodium", None)],
+				expected: Expected::Resolved(&["sodium 0.5.3"], &["iris"]),
+			},
+			Case {
+				name: "optional dependencies that are installed aren't suggested",
+				available: vec![version("sodium", "0.5.3", &[("iris", None, Optional)])],
+				installed: vec![version("iris", "1.6.4", &[])],
+				requests: vec![request("sodium", None)],
+				expected: Expected::Resolved(&["sodium 0.5.3"], &[]),
+			},
+			Case {
+				// `fabric-api` isn't available at all, so it would have to be fetched if it wasn't ignored.
+				name: "embedded dependencies are ignored",
+				available: vec![version(
+					"create",
+					"0.5.1",
+					&[("fabric-api", None, Embedded)],
+				)],
+				installed: vec![],
+				requests: vec![request("create", None)],
+				expected: Expected::Resolved(&["create 0.5.1"], &[]),
+			},
+			Case {
+				name: "incompatible with an installed mod",
+				available: vec![version(
+					"optifabric",
+					"1.0",
+					&[("sodium", None, Incompatible)],
+				)],
+				installed: vec![version("sodium", "0.5.3", &[])],
+				requests: vec![request("optifabric", None)],
+				expected: Expected::Incompatible,
+			},
+			Case {
+				name: "an installed mod incompatible with the new one",
+				available: vec![version("sodium", "0.5.3", &[])],
+				installed: vec![version(
+					"optifabric",
+					"1.0",
+					&[("sodium", None, Incompatible)],
+				)],
+				requests: vec![request("sodium", None)],
+				expected: Expected::Incompatible,
+			},
+			Case {
+				name: "incompatible with a specific version, which is avoided",
+				available: vec![
+					version(
+						"iris",
+						"1.6.4",
+						&[("sodium", Some("sodium-0.5.4"), Incompatible)],
+					),
+					version("sodium", "0.5.4", &[]),
+					version("sodium", "0.5.3", &[]),
+				],
+				installed: vec![],
+				requests: vec![request("iris", None), request("sodium", None)],
+				expected: Expected::Resolved(&["iris 1.6.4", "sodium 0.5.3"], &[]),
+			},
+			Case {
+				name: "no version meets every constraint",
+				available: vec![
+					version("iris", "1.6.4", &[("sodium", Some("0.5.3"), Required)]),
+					version("indium", "1.0.27", &[("sodium", Some("0.5.4"), Required)]),
+					version("sodium", "0.5.4", &[]),
+					version("sodium", "0.5.3", &[]),
+				],
+				installed: vec![],
+				requests: vec![request("iris", None), request("indium", None)],
+				expected: Expected::Unsatisfiable,
+			},
+			Case {
+				name: "a requested version that doesn't exist",
+				available: vec![version("sodium", "0.5.3", &[])],
+				installed: vec![],
+				requests: vec![request("sodium", Some("0.6.0"))],
+				expected: Expected::Unsatisfiable,
+			},
+			Case {
+				name: "an installed version violates a constraint",
+				available: vec![version(
+					"iris",
+					"1.6.4",
+					&[("sodium", Some("0.5.4"), Required)],
+				)],
+				installed: vec![version("sodium", "0.5.3", &[])],
+				requests: vec![request("iris", None)],
+				expected: Expected::Installed,
+			},
+			Case {
+				name: "installed versions are kept",
+				available: vec![
+					version("iris", "1.6.4", &[("sodium", None, Required)]),
+					version("sodium", "0.5.4", &[]),
+				],
+				installed: vec![version("sodium", "0.5.3", &[])],
+				requests: vec![request("iris", None)],
+				expected: Expected::Resolved(&["iris 1.6.4"], &[]),
+			},
+			Case {
+				name: "requesting an installed project updates it",
+				available: vec![
+					version("sodium", "0.5.4", &[]),
+					version("sodium", "0.5.3", &[]),
+				],
+				installed: vec![version("sodium", "0.5.3", &[])],
+				requests: vec![request("sodium", None)],
+				expected: Expected::Resolved(&["sodium 0.5.4"], &[]),
+			},
+			Case {
+				name: "backtracks onto an older dependent",
+				available: vec![
+					version("iris", "1.7.0", &[("sodium", Some("0.5.4"), Required)]),
+					version("iris", "1.6.4", &[("sodium", Some("0.5.3"), Required)]),
+					version("indium", "1.0.27", &[("sodium", Some("0.5.3"), Required)]),
+					version("sodium", "0.5.4", &[]),
+					version("sodium", "0.5.3", &[]),
+				],
+				installed: vec![],
+				requests: vec![request("iris", None), request("indium", None)],
+				expected: Expected::Resolved(&["indium 1.0.27", "iris 1.6.4", "sodium 0.5.3"], &[]),
+			},
+			Case {
+				name: "backtracks away from an incompatible dependent",
+				available: vec![
+					version("iris", "1.7.0", &[("optifabric", None, Incompatible)]),
+					version("iris", "1.6.4", &[]),
+				],
+				installed: vec![version("optifabric", "1.0", &[])],
+				requests: vec![request("iris", None)],
+				expected: Expected::Resolved(&["iris 1.6.4"], &[]),
+			},
+		];
+
+		for case in cases {
+			let mut available: HashMap<String, Vec<Candidate<()>>> = HashMap::new();
+			for candidate in case.available {
+				available
+					.entry(candidate.project.clone())
+					.or_default()
+					.push(candidate);
+			}
+			let result = resolve(&available, &case.requests, &case.installed);
+			let name = case.name;
+			match (result, case.expected) {
+				(Ok(Step::Resolved(resolution)), Expected::Resolved(install, suggestions)) => {
+					let installed: Vec<String> =
+						resolution.install.iter().map(|c| c.to_string()).collect();
+					let suggested: Vec<&str> = resolution
+						.suggestions
+						.iter()
+						.map(|suggestion| suggestion.project.as_str())
+						.collect();
+					assert_eq!(installed, install, "{name}");
+					assert_eq!(suggested, suggestions, "{name}");
+				}
+				(Ok(Step::Fetch(projects)), _) => panic!("{name}: asked to fetch {projects:?}"),
+				(Err(ResolveError::Unsatisfiable { .. }), Expected::Unsatisfiable)
+				| (Err(ResolveError::Installed { .. }), Expected::Installed)
+				| (Err(ResolveError::Incompatible { .. }), Expected::Incompatible) => {}
+				(Ok(Step::Resolved(resolution)), _) => {
+					let installed: Vec<String> =
+						resolution.install.iter().map(|c| c.to_string()).collect();
+					panic!("{name}: resolved to {installed:?}")
+				}
+				(Err(error), _) => panic!("{name}: {error}"),
+			}
+		}
+	}
+
+	#[test]
+	fn fetches_what_it_needs() {
+		let mut available = HashMap::new();
+		let requests = [request("sodium", None)];
+		match resolve::<()>(&available, &requests, &[]) {
+			Ok(Step::Fetch(projects)) => assert_eq!(projects, ["sodium"]),
+			_ => panic!("expected to fetch sodium"),
+		}
+
+		available.insert(
+			"sodium".to_string(),
+			vec![version(
+				"sodium",
+				"0.5.3",
+				&[("fabric-api", None, Required)],
+			)],
+		);
+		match resolve(&available, &requests, &[]) {
+			Ok(Step::Fetch(projects)) => assert_eq!(projects, ["fabric-api"]),
+			_ => panic!("expected to fetch fabric-api"),
+		}
+	}
+}
@@ -4,6 +4,7 @@ use crate::Result;
 use data_encoding::HEXLOWER;
 use lazy_static::lazy_static;
 use serde::de::DeserializeOwned;
+use sha1::Sha1;
 use sha2::{Digest, Sha512};
 use std::{
 	collections::HashMap,
@@ -11,10 +12,19 @@ use std::{
 	fmt,
 	fs::{self, File},
 	io::Write,
-	os::unix,
-	path::PathBuf,
+	os::unix::{
+		self,
+		fs::{MetadataExt, PermissionsExt},
+	},
+	path::{Path, PathBuf},
+};
+use std::{
+	io,
+	sync::{
+		atomic::{AtomicBool, Ordering},
+		Mutex,
+	},
 };
-use std::{io, sync::Mutex};
 use tracing::warn;
 
 lazy_static! {
@@ -30,6 +40,23 @@ lazy_static! {
 	};
 }
 
+/// Whether downloads are turned off, see [set_offline].
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+/// Turn downloads off (or back on), so only what's already in the store is used, such as for a dry run.
+/// Anything that would be downloaded is an [GetError::Offline] error instead.
+pub fn set_offline(offline: bool) {
+	OFFLINE.store(offline, Ordering::Relaxed);
+}
+
+/// Check that downloads are allowed, before downloading `url`.
+pub fn check_online(url: &str) -> Result<()> {
+	if OFFLINE.load(Ordering::Relaxed) {
+		return Err(Box::new(GetError::Offline(url.to_string())));
+	}
+	Ok(())
+}
+
 pub fn save_url_cache() -> Result<()> {
 	let file = File::create(STORE_BASE.join("cache.json"))?;
 	serde_json::to_writer(file, &*URL_CACHE)?;
@@ -67,16 +94,42 @@ pub fn save(data: impl AsRef<[u8]>, input: StoreInput) -> Result<StoreOutput> {
 	Ok(StoreOutput { path, sha512 })
 }
 
+/// Save data that didn't come from a URL (such as files made by a mod loader's installer), so it can only be found by its sha512.
+pub fn import(data: impl AsRef<[u8]>) -> Result<StoreOutput> {
+	let mut hasher = Sha512::new();
+	hasher.update(&data);
+	save(
+		data,
+		StoreInput::Sha512(HEXLOWER.encode(&hasher.finalize())),
+	)
+}
+
 #[derive(Debug)]
 pub enum GetError {
 	CorruptedFile,
+	Sha1Mismatch(String),
+	Sha512Mismatch(String),
+	DownloadSha1Mismatch(String),
 	URLNotInCache,
+	Offline(String),
 }
 impl fmt::Display for GetError {
 	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
 		match self {
 			Self::CorruptedFile => write!(f, "File in store is corrupted"),
+			Self::Sha1Mismatch(sha512) => {
+				write!(f, "File {sha512} in store doesn't match its expected sha1")
+			}
+			Self::Sha512Mismatch(url) => {
+				write!(f, "Download from {url} doesn't match its expected sha512")
+			}
+			Self::DownloadSha1Mismatch(url) => {
+				write!(f, "Download from {url} doesn't match its expected sha1")
+			}
 			Self::URLNotInCache => write!(f, "Requested URL not in cache"),
+			Self::Offline(url) => {
+				write!(f, "{url} isn't in the store, and downloads are turned off")
+			}
 		}
 	}
 }
@@ -109,6 +162,7 @@ pub fn get(input: StoreInput) -> Result<StoreOutput> {
 }
 
 /// Get data from the store, or download it as a fallback.
+#[allow(clippy::unnecessary_unwrap)]
 pub async fn fetch(sha512: Option<String>, url: String) -> Result<StoreOutput> {
 	if let Some(sha512) = &sha512 {
 		let store = get(StoreInput::Sha512(sha512.to_string()));
@@ -128,6 +182,7 @@ pub async fn fetch(sha512: Option<String>, url: String) -> Result<StoreOutput> {
 	}
 
 	// info!("Downloading {url}...");
+	check_online(&url)?;
 	let data = reqwest::get(&url).await?.bytes().await?;
 	let input = match sha512 {
 		Some(hash) => StoreInput::Sha512(hash),
@@ -136,10 +191,71 @@ pub async fn fetch(sha512: Option<String>, url: String) -> Result<StoreOutput> {
 	save(&data, input)
 }
 
+/// Get a file from the store by its sha512, or download it and check it has that sha512.
+/// Unlike [fetch], the download is never trusted, for files that have to be exactly what was asked for.
+pub async fn fetch_verified(sha512: String, url: String) -> Result<StoreOutput> {
+	if let Ok(store) = get(StoreInput::Sha512(sha512.clone())) {
+		return Ok(store);
+	}
+	check_online(&url)?;
+	let data = reqwest::get(&url)
+		.await?
+		.error_for_status()?
+		.bytes()
+		.await?;
+	// Checked before saving, so a bad download never reaches the store or the URL cache.
+	let mut hasher = Sha512::new();
+	hasher.update(&data);
+	if HEXLOWER.encode(&hasher.finalize()) != sha512 {
+		return Err(Box::new(GetError::Sha512Mismatch(url)));
+	}
+	save(&data, StoreInput::URL(url))
+}
+
+/// Get a file from the store by its URL, or download it and check it has the sha1 an API gave for it.
+/// Like [fetch_verified], a bad download never reaches the store or the URL cache.
+pub async fn fetch_sha1(sha1: String, url: String) -> Result<StoreOutput> {
+	if let Ok(store) = get(StoreInput::URL(url.clone())) {
+		if verify_sha1(&store, &sha1).is_ok() {
+			return Ok(store);
+		}
+	}
+	check_online(&url)?;
+	let data = reqwest::get(&url)
+		.await?
+		.error_for_status()?
+		.bytes()
+		.await?;
+	let mut hasher = Sha1::new();
+	hasher.update(&data);
+	if HEXLOWER.encode(&hasher.finalize()) != sha1 {
+		return Err(Box::new(GetError::DownloadSha1Mismatch(url)));
+	}
+	save(&data, StoreInput::URL(url))
+}
+
+/// Whether a path is a symlink into the store.
+pub fn is_stored(path: &Path) -> bool {
+	fs::read_link(path).is_ok_and(|target| target.starts_with(&*STORE_BASE))
+}
+
 /// Save data from a URL to the store, always updating the sha512 cache.
+/// While [offline](set_offline), or if the download fails, the copy that's already in the store is used instead.
 pub async fn force_update(url: String) -> Result<StoreOutput> {
-	let data = reqwest::get(&url).await?.bytes().await?;
-	save(&data, StoreInput::URL(url))
+	if check_online(&url).is_err() {
+		return get(StoreInput::URL(url.clone())).map_err(|_| GetError::Offline(url).into());
+	}
+	let download = async { reqwest::get(&url).await?.error_for_status()?.bytes().await };
+	match download.await {
+		Ok(data) => save(&data, StoreInput::URL(url)),
+		Err(err) => match get(StoreInput::URL(url.clone())) {
+			Ok(store) => {
+				warn!("Couldn't update {url}, using the copy in the store: {err}");
+				Ok(store)
+			}
+			Err(_) => Err(Box::new(err)),
+		},
+	}
 }
 
 /// Get data from the store and deserialize it to a struct.
@@ -153,6 +269,17 @@ where
 	}
 }
 
+/// Download data that changes often (like lists of versions) and deserialize it, keeping a copy in the store for when
+/// downloads are turned off.
+pub async fn fetch_fresh_data<T>(url: String) -> Result<T>
+where
+	T: DeserializeOwned,
+{
+	Ok(serde_json::from_reader(File::open(
+		force_update(url).await?.path,
+	)?)?)
+}
+
 /// Fetch a file, and symlink it to a directory.
 pub async fn update_file(
 	sha512: Option<String>,
@@ -168,3 +295,60 @@ pub async fn update_file(
 	unix::fs::symlink(&store.path, path)?;
 	Ok(store)
 }
+
+/// Symlink a file from the store to a path, replacing anything already there.
+pub fn symlink(store: &StoreOutput, path: &Path) -> Result<()> {
+	if path.symlink_metadata().is_ok() {
+		fs::remove_file(path)?;
+	}
+	fs::create_dir_all(path.parent().unwrap())?;
+	unix::fs::symlink(&store.path, path)?;
+	Ok(())
+}
+
+/// Check a file in the store against the sha1 an API gave for it.
+pub fn verify_sha1(store: &StoreOutput, sha1: &str) -> Result<()> {
+	let mut hasher = Sha1::new();
+	io::copy(&mut File::open(&store.path)?, &mut hasher)?;
+	if HEXLOWER.encode(&hasher.finalize()) != sha1 {
+		return Err(Box::new(GetError::Sha1Mismatch(store.sha512.clone())));
+	}
+	Ok(())
+}
+
+/// Hardlink a file from the store to a path, replacing anything already there unless it's already the same file.
+/// Used instead of symlinks for files that care about their real location, such as Java executables.
+/// Falls back to copying if the path is on a different filesystem.
+pub fn hard_link(store: &StoreOutput, path: &Path, executable: bool) -> Result<()> {
+	// Hardlinks share permissions, so executables link to their own copy rather than making the shared file executable.
+	let source = match executable {
+		true => executable_copy(store)?,
+		false => store.path.clone(),
+	};
+
+	if let Ok(metadata) = path.symlink_metadata() {
+		if metadata.ino() == fs::metadata(&source)?.ino() {
+			return Ok(());
+		}
+		fs::remove_file(path)?;
+	}
+	fs::create_dir_all(path.parent().unwrap())?;
+	if fs::hard_link(&source, path).is_err() {
+		fs::copy(&source, path)?;
+	}
+	Ok(())
+}
+
+/// An executable copy of a file in the store, kept under `{STORE_BASE}/executable`.
+fn executable_copy(store: &StoreOutput) -> Result<PathBuf> {
+	let path = STORE_BASE.join("executable").join(&store.sha512);
+	if !path.exists() {
+		fs::create_dir_all(path.parent().unwrap())?;
+		// Copied under another name first, so an interrupted copy is never mistaken for a finished one.
+		let partial = path.with_extension("partial");
+		fs::copy(&store.path, &partial)?;
+		fs::set_permissions(&partial, fs::Permissions::from_mode(0o755))?;
+		fs::rename(partial, &path)?;
+	}
+	Ok(path)
+}
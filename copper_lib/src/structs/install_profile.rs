@@ -0,0 +1,172 @@
+use super::profile::Library;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// `install_profile.json` from a Forge or NeoForge installer (spec 1 and later, for Minecraft 1.13+).
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct InstallProfile {
+	#[serde(default)]
+	pub spec: i32,
+	/// ID of the version profile the installer makes.
+	pub version: String,
+	/// The Minecraft version being patched.
+	pub minecraft: String,
+	/// Path of the version profile within the installer, usually `/version.json`.
+	pub json: String,
+	/// Values that processor arguments refer to as `{KEY}`.
+	#[serde(default)]
+	pub data: HashMap<String, DataEntry>,
+	#[serde(default)]
+	pub processors: Vec<Processor>,
+	/// Libraries the processors need. These aren't added to the game's classpath.
+	#[serde(default)]
+	pub libraries: Vec<Library>,
+}
+
+/// A data value, which differs between the client and server. Values are either:
+/// - `[group:artifact:version]`: the path of a library.
+/// - `'text'`: a literal.
+/// - `/path`: a file within the installer.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DataEntry {
+	pub client: String,
+	pub server: String,
+}
+
+/// A Java program run to patch the game, such as deobfuscating the client jar and applying Forge's binary patches.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Processor {
+	/// `client` and/or `server`. Processors without sides run for both.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub sides: Option<Vec<String>>,
+	/// Maven coordinate of the processor's jar, which is one of the install profile's libraries.
+	pub jar: String,
+	#[serde(default)]
+	pub classpath: Vec<String>,
+	#[serde(default)]
+	pub args: Vec<String>,
+	/// Files the processor makes, and their expected sha1. Both can refer to data values.
+	#[serde(default)]
+	pub outputs: HashMap<String, String>,
+}
+
+impl Processor {
+	pub fn runs_on_client(&self) -> bool {
+		self.sides
+			.as_ref()
+			.is_none_or(|sides| sides.iter().any(|side| side == "client"))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// Shaped like the install profile of Forge's 1.20.1-47.1.0 installer, trimmed down.
+	const FORGE: &str = r#"{
+		"_comment": ["Please do not automate the download and installation of Forge."],
+		"spec": 1,
+		"profile": "forge",
+		"version": "1.20.1-forge-47.1.0",
+		"path": null,
+		"minecraft": "1.20.1",
+		"serverJarPath": "{LIBRARY_DIR}/net/minecraft/server/{MINECRAFT_VERSION}/server-{MINECRAFT_VERSION}.jar",
+		"data": {
+			"MAPPINGS": {
+				"client": "[de.oceanlabs.mcp:mcp_config:1.20.1-20230612.114412:mappings@txt]",
+				"server": "[de.oceanlabs.mcp:mcp_config:1.20.1-20230612.114412:mappings@txt]"
+			},
+			"BINPATCH": {
+				"client": "/data/client.lzma",
+				"server": "/data/server.lzma"
+			},
+			"PATCHED_SHA": {
+				"client": "'0f3e7c2b57a1cb02a1a8d3ef62bff74e4ba5e1e5'",
+				"server": "'5e1e5ab4e47ffb26fe3d8a1a20bc1a75b2c7e3f0'"
+			}
+		},
+		"processors": [
+			{
+				"sides": ["server"],
+				"jar": "net.minecraftforge:installertools:1.3.0",
+				"classpath": ["net.md-5:SpecialSource:1.11.0"],
+				"args": ["--task", "EXTRACT_FILES"]
+			},
+			{
+				"jar": "net.minecraftforge:installertools:1.3.0",
+				"classpath": ["net.md-5:SpecialSource:1.11.0"],
+				"args": ["--task", "MCP_DATA", "--input", "[de.oceanlabs.mcp:mcp_config:1.20.1-20230612.114412@zip]", "--output", "{MAPPINGS}", "--key", "mappings"]
+			},
+			{
+				"sides": ["client"],
+				"jar": "net.minecraftforge:binarypatcher:1.1.1",
+				"classpath": ["commons-io:commons-io:2.4"],
+				"args": ["--clean", "{MC_SRG}", "--output", "{PATCHED}", "--apply", "{BINPATCH}"],
+				"outputs": {"{PATCHED}": "{PATCHED_SHA}"}
+			}
+		],
+		"libraries": [
+			{
+				"name": "net.minecraftforge:installertools:1.3.0",
+				"downloads": {
+					"artifact": {
+						"path": "net/minecraftforge/installertools/1.3.0/installertools-1.3.0.jar",
+						"url": "https://maven.minecraftforge.net/net/minecraftforge/installertools/1.3.0/installertools-1.3.0.jar",
+						"sha1": "cd5f3c6b7a0e3f6f1b2d4c6e9b4a3b1f8e7d6c5a",
+						"size": 470934
+					}
+				}
+			}
+		],
+		"icon": "data:image/png;base64,",
+		"json": "/version.json",
+		"logo": "/big_logo.png",
+		"mirrorList": "https://files.minecraftforge.net/mirrors-2.0.json",
+		"welcome": "Welcome to the simple Forge installer."
+	}"#;
+
+	#[test]
+	fn parses_install_profiles() {
+		let profile: InstallProfile = serde_json::from_str(FORGE).unwrap();
+		assert_eq!(profile.spec, 1);
+		assert_eq!(profile.version, "1.20.1-forge-47.1.0");
+		assert_eq!(profile.minecraft, "1.20.1");
+		assert_eq!(profile.json, "/version.json");
+		assert_eq!(profile.data["BINPATCH"].client, "/data/client.lzma");
+		assert_eq!(profile.data["BINPATCH"].server, "/data/server.lzma");
+		assert_eq!(profile.libraries.len(), 1);
+		assert_eq!(
+			profile.libraries[0].name,
+			"net.minecraftforge:installertools:1.3.0"
+		);
+
+		let [server, both, client] = &profile.processors[..] else {
+			panic!("expected 3 processors");
+		};
+		assert!(!server.runs_on_client());
+		assert!(both.runs_on_client());
+		assert!(client.runs_on_client());
+		assert!(both.outputs.is_empty());
+		assert_eq!(client.outputs["{PATCHED}"], "{PATCHED_SHA}");
+		assert_eq!(client.classpath, ["commons-io:commons-io:2.4"]);
+	}
+
+	#[test]
+	fn optional_fields_default() {
+		let profile: InstallProfile = serde_json::from_str(
+			r#"{"version": "1.20.1-neoforge-47.1.79", "minecraft": "1.20.1", "json": "/version.json"}"#,
+		)
+		.unwrap();
+		assert_eq!(profile.spec, 0);
+		assert!(profile.data.is_empty());
+		assert!(profile.processors.is_empty());
+		assert!(profile.libraries.is_empty());
+	}
+
+	#[test]
+	fn rejects_old_installers() {
+		// Installers for 1.12.2 and older have an `install` section instead, which is unsupported.
+		let old = r#"{"install": {"profileName": "Forge", "target": "1.12.2-forge-14.23.5.2860"}, "versionInfo": {}}"#;
+		assert!(serde_json::from_str::<InstallProfile>(old).is_err());
+	}
+}
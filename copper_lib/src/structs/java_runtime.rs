@@ -0,0 +1,56 @@
+use super::profile::Download;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Every Java runtime Mojang distributes, by platform (`linux`, `mac-os-arm64`, `windows-x64`...) and then by component (`java-runtime-gamma`, `jre-legacy`...).
+pub type JavaRuntimes = HashMap<String, HashMap<String, Vec<RuntimeEntry>>>;
+
+/// A release of a runtime component for one platform.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RuntimeEntry {
+	pub availability: Availability,
+	pub manifest: Download,
+	pub version: RuntimeVersion,
+}
+
+/// Used by Mojang's launcher for staged rollouts.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Availability {
+	pub group: i32,
+	pub progress: i32,
+}
+
+/// `name`: Full Java version, e.g. `17.0.8`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RuntimeVersion {
+	pub name: String,
+	pub released: String,
+}
+
+/// Every file in a runtime, by path relative to the runtime's root.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RuntimeManifest {
+	pub files: HashMap<String, RuntimeFile>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum RuntimeFile {
+	Directory,
+	File {
+		downloads: Box<RuntimeDownloads>,
+		#[serde(default)]
+		executable: bool,
+	},
+	Link {
+		target: String,
+	},
+}
+
+/// The uncompressed file, and sometimes an LZMA compressed version of it.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RuntimeDownloads {
+	pub raw: Download,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub lzma: Option<Download>,
+}
@@ -0,0 +1,186 @@
+use serde::{Deserialize, Serialize};
+use std::{error::Error, fmt, str::FromStr};
+
+/// A Maven artifact, written as `group:artifact:version[:classifier][@extension]`.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq, Hash)]
+#[serde(try_from = "String", into = "String")]
+pub struct MavenCoordinate {
+	pub group: String,
+	pub artifact: String,
+	pub version: String,
+	pub classifier: Option<String>,
+	/// File extension, `jar` unless given with `@`.
+	pub extension: String,
+}
+
+#[derive(Debug)]
+pub enum MavenCoordinateError {
+	Invalid(String),
+}
+impl fmt::Display for MavenCoordinateError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Invalid(coordinate) => write!(
+				f,
+				"Invalid Maven coordinate `{coordinate}`, expected group:artifact:version[:classifier][@extension]"
+			),
+		}
+	}
+}
+impl Error for MavenCoordinateError {}
+
+impl MavenCoordinate {
+	/// Path of the artifact within a repository, e.g. `org/ow2/asm/asm/9.6/asm-9.6.jar`.
+	pub fn path(&self) -> String {
+		let classifier = match &self.classifier {
+			Some(classifier) => format!("-{classifier}"),
+			None => String::new(),
+		};
+		format!(
+			"{}/{}/{}/{}-{}{classifier}.{}",
+			self.group.replace('.', "/"),
+			self.artifact,
+			self.version,
+			self.artifact,
+			self.version,
+			self.extension
+		)
+	}
+
+	/// URL of the artifact in a repository, which may or may not end with a `/`.
+	pub fn url(&self, repository: &str) -> String {
+		format!("{}/{}", repository.trim_end_matches('/'), self.path())
+	}
+}
+
+impl FromStr for MavenCoordinate {
+	type Err = MavenCoordinateError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let invalid = || MavenCoordinateError::Invalid(s.to_string());
+		let (coordinate, extension) = match s.split_once('@') {
+			Some((coordinate, extension)) => (coordinate, extension),
+			None => (s, "jar"),
+		};
+		let parts: Vec<&str> = coordinate.split(':').collect();
+		if parts.iter().any(|part| part.is_empty()) || extension.is_empty() {
+			return Err(invalid());
+		}
+		let (group, artifact, version, classifier) = match parts[..] {
+			[group, artifact, version] => (group, artifact, version, None),
+			[group, artifact, version, classifier] => (group, artifact, version, Some(classifier)),
+			_ => return Err(invalid()),
+		};
+		Ok(Self {
+			group: group.to_string(),
+			artifact: artifact.to_string(),
+			version: version.to_string(),
+			classifier: classifier.map(String::from),
+			extension: extension.to_string(),
+		})
+	}
+}
+impl TryFrom<String> for MavenCoordinate {
+	type Error = MavenCoordinateError;
+
+	fn try_from(value: String) -> Result<Self, Self::Error> {
+		value.parse()
+	}
+}
+
+impl fmt::Display for MavenCoordinate {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{}:{}:{}", self.group, self.artifact, self.version)?;
+		if let Some(classifier) = &self.classifier {
+			write!(f, ":{classifier}")?;
+		}
+		if self.extension != "jar" {
+			write!(f, "@{}", self.extension)?;
+		}
+		Ok(())
+	}
+}
+impl From<MavenCoordinate> for String {
+	fn from(coordinate: MavenCoordinate) -> Self {
+		coordinate.to_string()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parses_coordinates() {
+		for (coordinate, path) in [
+			("org.ow2.asm:asm:9.6", "org/ow2/asm/asm/9.6/asm-9.6.jar"),
+			(
+				"org.lwjgl:lwjgl:3.3.1:natives-linux",
+				"org/lwjgl/lwjgl/3.3.1/lwjgl-3.3.1-natives-linux.jar",
+			),
+			(
+				"de.oceanlabs.mcp:mcp_config:1.20.1-20230612.114412@zip",
+				"de/oceanlabs/mcp/mcp_config/1.20.1-20230612.114412/mcp_config-1.20.1-20230612.114412.zip",
+			),
+			(
+				"net.minecraft:client:1.20.1-20230612.114412:mappings@txt",
+				"net/minecraft/client/1.20.1-20230612.114412/client-1.20.1-20230612.114412-mappings.txt",
+			),
+		] {
+			let parsed: MavenCoordinate = coordinate.parse().unwrap();
+			assert_eq!(parsed.path(), path);
+			// Writing a coordinate back out gives what it was parsed from.
+			assert_eq!(parsed.to_string(), coordinate);
+		}
+	}
+
+	#[test]
+	fn parses_parts() {
+		let coordinate: MavenCoordinate =
+			"net.minecraft:client:1.20.1:mappings@txt".parse().unwrap();
+		assert_eq!(
+			coordinate,
+			MavenCoordinate {
+				group: "net.minecraft".into(),
+				artifact: "client".into(),
+				version: "1.20.1".into(),
+				classifier: Some("mappings".into()),
+				extension: "txt".into(),
+			}
+		);
+		assert_eq!(
+			coordinate.url("https://maven.minecraftforge.net/"),
+			"https://maven.minecraftforge.net/net/minecraft/client/1.20.1/client-1.20.1-mappings.txt"
+		);
+	}
+
+	#[test]
+	fn rejects_invalid_coordinates() {
+		for coordinate in [
+			"",
+			"org.ow2.asm",
+			"org.ow2.asm:asm",
+			"org.ow2.asm::9.6",
+			"org.ow2.asm:asm:9.6:",
+			"org.ow2.asm:asm:9.6@",
+			"org.ow2.asm:asm:9.6:natives:extra",
+		] {
+			assert!(
+				coordinate.parse::<MavenCoordinate>().is_err(),
+				"{coordinate}"
+			);
+		}
+	}
+
+	#[test]
+	fn deserializes_from_strings() {
+		let coordinate: MavenCoordinate = serde_json::from_str("\"org.ow2.asm:asm:9.6\"").unwrap();
+		assert_eq!(coordinate.classifier, None);
+		assert_eq!(coordinate.extension, "jar");
+		assert!(serde_json::from_str::<MavenCoordinate>("\"asm\"").is_err());
+		assert_eq!(
+			serde_json::to_string(&coordinate).unwrap(),
+			"\"org.ow2.asm:asm:9.6\""
+		);
+	}
+}
@@ -5,21 +5,52 @@
 //! [Manifest]: Fetched from <https://launchermeta.mojang.com/mc/game/version_manifest.json>.
 //!
 //! [Profile]: Fetched from URLs contained in the [Manifest].
+//!
+//! [JavaRuntimes]: Fetched from <https://launchermeta.mojang.com/v1/products/java-runtime/2ec0cc96c44e5a76b9c8b7c39df7210883d12871/all.json>.
 
 pub mod asset_index;
 pub use asset_index::AssetIndex;
 
+pub mod install_profile;
+
+pub mod java_runtime;
+pub use java_runtime::JavaRuntimes;
+
+pub mod maven;
+pub use maven::MavenCoordinate;
+
+pub mod mod_metadata;
+
 pub mod version_manifest;
 pub use version_manifest::VersionManifest;
 
+pub mod partial_profile;
+pub use partial_profile::PartialProfile;
+
 pub mod profile;
 pub use profile::Profile;
 use profile::*;
 
+/// Launcher features that argument rules can be conditional on.
+///
+/// `is_demo_user`: If the Minecraft is running in demo mode.
+/// `has_custom_resolution`: If a custom resolution is being passed to Minecraft.
+/// `has_quick_plays_support`: If the launcher passes a path for Minecraft to write its quick play log to.
+/// `is_quick_play_*`: If Minecraft should launch straight into a singleplayer world, a server, or a Realm.
+#[derive(Clone, Debug, Default)]
+pub struct Features {
+	pub is_demo_user: bool,
+	pub has_custom_resolution: bool,
+	pub has_quick_plays_support: bool,
+	pub is_quick_play_singleplayer: bool,
+	pub is_quick_play_multiplayer: bool,
+	pub is_quick_play_realms: bool,
+}
+
 impl Rule {
-	pub fn is_true(&self, demo: bool, custom_resolution: bool) -> bool {
+	pub fn is_true(&self, features: &Features) -> bool {
 		for rule in &self.rules {
-			if !rule.is_true(demo, custom_resolution) {
+			if !rule.is_true(features) {
 				return false;
 			}
 		}
@@ -28,9 +59,9 @@ impl Rule {
 }
 
 impl RuleItem {
-	pub fn is_true(&self, demo: bool, custom_resolution: bool) -> bool {
+	pub fn is_true(&self, features: &Features) -> bool {
 		let features = match &self.features {
-			Some(features) => features.is_true(demo, custom_resolution),
+			Some(rule_features) => rule_features.is_true(features),
 			None => true,
 		};
 		let os = match &self.os {
@@ -46,17 +77,26 @@ impl RuleItem {
 }
 
 impl RuleItemFeatures {
-	pub fn is_true(&self, demo: bool, custom_resolution: bool) -> bool {
-		let demo = match self.is_demo_user {
-			Some(is_demo_user) => demo == is_demo_user,
-			None => true,
-		};
-		let custom_resolution = match self.has_custom_resolution {
-			Some(has_custom_resolution) => custom_resolution == has_custom_resolution,
-			None => true,
-		};
-
-		demo && custom_resolution
+	pub fn is_true(&self, features: &Features) -> bool {
+		[
+			(self.is_demo_user, features.is_demo_user),
+			(self.has_custom_resolution, features.has_custom_resolution),
+			(
+				self.has_quick_plays_support,
+				features.has_quick_plays_support,
+			),
+			(
+				self.is_quick_play_singleplayer,
+				features.is_quick_play_singleplayer,
+			),
+			(
+				self.is_quick_play_multiplayer,
+				features.is_quick_play_multiplayer,
+			),
+			(self.is_quick_play_realms, features.is_quick_play_realms),
+		]
+		.into_iter()
+		.all(|(rule, value)| rule.is_none_or(|rule| rule == value))
 	}
 }
 
@@ -92,8 +132,8 @@ impl Library {
 		let mut active = true;
 		if let Some(rules) = &self.rules {
 			for rule in rules {
-				// The `demo` and `custom_resolution` values only show up on rules applied to java arguments, so they don't matter for library rules.
-				if !rule.is_true(false, false) {
+				// Features only show up on rules applied to java arguments, so they don't matter for library rules.
+				if !rule.is_true(&Features::default()) {
 					active = false;
 					break;
 				}
@@ -101,4 +141,28 @@ impl Library {
 		}
 		active
 	}
+
+	pub fn coordinate(&self) -> Result<MavenCoordinate, maven::MavenCoordinateError> {
+		self.name.parse()
+	}
+}
+
+impl Profile {
+	/// Whether this version understands the `--quickPlay*` arguments (1.20 and newer).
+	/// Older versions only support joining a server through `--server` and `--port`.
+	pub fn supports_quick_play(&self) -> bool {
+		let arguments = match &self.arguments {
+			Arguments::NewArguments(arguments) => arguments,
+			Arguments::OldArguments(_) => return false,
+		};
+
+		arguments.game.iter().any(|argument| match argument {
+			Argument::Rule(rule) => rule.rules.iter().any(|item| {
+				item.features
+					.as_ref()
+					.is_some_and(|features| features.has_quick_plays_support.is_some())
+			}),
+			Argument::String(_) => false,
+		})
+	}
 }
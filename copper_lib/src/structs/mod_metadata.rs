@@ -0,0 +1,70 @@
+//! Metadata that mod loaders read from inside mod jars.
+
+use serde::{Deserialize, Serialize};
+
+/// `fabric.mod.json`, from Fabric mods.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct FabricModJson {
+	pub id: String,
+	pub version: String,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub name: Option<String>,
+}
+
+/// `quilt.mod.json`, from Quilt mods.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct QuiltModJson {
+	pub quilt_loader: QuiltLoader,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct QuiltLoader {
+	pub id: String,
+	pub version: String,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub metadata: Option<QuiltMetadata>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct QuiltMetadata {
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub name: Option<String>,
+}
+
+/// `META-INF/mods.toml`, from Forge mods, or `META-INF/neoforge.mods.toml` from NeoForge ones. One jar can hold several mods.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ModsToml {
+	#[serde(default)]
+	pub mods: Vec<ModsTomlEntry>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModsTomlEntry {
+	pub mod_id: String,
+	/// Often `${file.jarVersion}`, which Forge fills in from the jar's manifest.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub version: Option<String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub display_name: Option<String>,
+}
+
+/// `mcmod.info`, from Forge mods for 1.12 and older. Either a list of mods, or an object holding one.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum McmodInfo {
+	List(Vec<McmodInfoEntry>),
+	Object {
+		#[serde(rename = "modList")]
+		mod_list: Vec<McmodInfoEntry>,
+	},
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct McmodInfoEntry {
+	pub modid: String,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub name: Option<String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub version: Option<String>,
+}
@@ -0,0 +1,477 @@
+use super::profile::*;
+use serde::{de, Deserialize, Deserializer, Serialize};
+use std::{error::Error, fmt};
+
+/// A profile that can inherit from another with `inheritsFrom`, like the ones mod loaders (Fabric, Quilt, Forge, OptiFine) ship.
+/// Anything it leaves out comes from its parent, see [PartialProfile::merge].
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PartialProfile {
+	pub id: String,
+	#[serde(alias = "inheritsFrom", skip_serializing_if = "Option::is_none")]
+	pub inherits_from: Option<String>,
+	#[serde(alias = "minecraftArguments", skip_serializing_if = "Option::is_none")]
+	pub arguments: Option<PartialArguments>,
+	#[serde(alias = "assetIndex", skip_serializing_if = "Option::is_none")]
+	pub asset_index: Option<Download>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub assets: Option<String>,
+	#[serde(alias = "complianceLevel", skip_serializing_if = "Option::is_none")]
+	pub compliance_level: Option<i32>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub downloads: Option<Downloads>,
+	#[serde(alias = "javaVersion", skip_serializing_if = "Option::is_none")]
+	pub java_version: Option<JavaVersion>,
+	#[serde(default)]
+	pub libraries: Vec<Library>,
+	#[serde(
+		default,
+		deserialize_with = "deserialize_logging",
+		skip_serializing_if = "Option::is_none"
+	)]
+	pub logging: Option<Logging>,
+	#[serde(alias = "mainClass", skip_serializing_if = "Option::is_none")]
+	pub main_class: Option<String>,
+	#[serde(
+		alias = "minimumLauncherVersion",
+		skip_serializing_if = "Option::is_none"
+	)]
+	pub minimum_launcher_version: Option<i32>,
+	#[serde(alias = "releaseTime", skip_serializing_if = "Option::is_none")]
+	pub release_time: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub time: Option<String>,
+	#[serde(alias = "type", skip_serializing_if = "Option::is_none")]
+	pub version_type: Option<String>,
+}
+
+/// Forge's profiles have `"logging": {}`, which leaves the parent's logging config in place.
+fn deserialize_logging<'de, D: Deserializer<'de>>(
+	deserializer: D,
+) -> Result<Option<Logging>, D::Error> {
+	match Option::<serde_json::Value>::deserialize(deserializer)? {
+		Some(serde_json::Value::Object(object)) if object.is_empty() => Ok(None),
+		Some(value) => serde_json::from_value(value).map_err(de::Error::custom),
+		None => Ok(None),
+	}
+}
+
+/// Like [Arguments], but either list can be left out.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum PartialArguments {
+	NewArguments(PartialNewArguments),
+	OldArguments(String),
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct PartialNewArguments {
+	#[serde(default)]
+	pub game: Vec<Argument>,
+	#[serde(default)]
+	pub jvm: Vec<Argument>,
+}
+
+#[derive(Debug)]
+pub enum PartialProfileError {
+	MissingField { id: String, field: &'static str },
+	MissingParent { id: String, parent: String },
+	InheritanceCycle(String),
+}
+impl fmt::Display for PartialProfileError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::MissingField { id, field } => write!(
+				f,
+				"Profile {id} is missing `{field}`, and doesn't inherit it from anything"
+			),
+			Self::MissingParent { id, parent } => {
+				write!(f, "Profile {id} inherits from unknown version {parent}")
+			}
+			Self::InheritanceCycle(id) => write!(f, "Profile {id} inherits from itself"),
+		}
+	}
+}
+impl Error for PartialProfileError {}
+
+/// JVM arguments the launcher adds itself for profiles with old-style `minecraftArguments`.
+fn old_jvm_arguments() -> Vec<Argument> {
+	[
+		"-Djava.library.path=${natives_directory}",
+		"-cp",
+		"${classpath}",
+	]
+	.into_iter()
+	.map(|arg| Argument::String(arg.into()))
+	.collect()
+}
+
+impl PartialProfile {
+	/// Merge this profile on top of its parent.
+	///
+	/// The child's libraries come first, with the parent's after them one level further up the chain (see [Library::inheritance_depth]).
+	/// New-style arguments are appended to the parent's, and everything else the child sets replaces the parent's.
+	pub fn merge(self, parent: Profile) -> Profile {
+		let arguments = match (self.arguments, parent.arguments) {
+			(None, parent) => parent,
+			(Some(PartialArguments::OldArguments(arguments)), _) => {
+				Arguments::OldArguments(arguments)
+			}
+			(Some(PartialArguments::NewArguments(child)), Arguments::NewArguments(mut parent)) => {
+				parent.game.extend(child.game);
+				parent.jvm.extend(child.jvm);
+				Arguments::NewArguments(parent)
+			}
+			// The parent's arguments need converting to the new format to have anything appended to them.
+			(Some(PartialArguments::NewArguments(child)), Arguments::OldArguments(parent)) => {
+				let mut game: Vec<Argument> = parent
+					.split(' ')
+					.map(|arg| Argument::String(arg.into()))
+					.collect();
+				game.extend(child.game);
+				let mut jvm = old_jvm_arguments();
+				jvm.extend(child.jvm);
+				Arguments::NewArguments(NewArguments { game, jvm })
+			}
+		};
+
+		let mut libraries = self.libraries;
+		libraries.extend(parent.libraries.into_iter().map(|mut library| {
+			library.inheritance_depth += 1;
+			library
+		}));
+
+		Profile {
+			arguments,
+			asset_index: self.asset_index.unwrap_or(parent.asset_index),
+			assets: self.assets.unwrap_or(parent.assets),
+			compliance_level: self.compliance_level.unwrap_or(parent.compliance_level),
+			downloads: self.downloads.unwrap_or(parent.downloads),
+			id: self.id,
+			java_version: self.java_version.unwrap_or(parent.java_version),
+			libraries,
+			logging: self.logging.or(parent.logging),
+			main_class: self.main_class.unwrap_or(parent.main_class),
+			minimum_launcher_version: self
+				.minimum_launcher_version
+				.unwrap_or(parent.minimum_launcher_version),
+			release_time: self.release_time.unwrap_or(parent.release_time),
+			time: self.time.unwrap_or(parent.time),
+			version_type: self.version_type.unwrap_or(parent.version_type),
+		}
+	}
+}
+
+impl TryFrom<PartialProfile> for Profile {
+	type Error = PartialProfileError;
+
+	/// Use a profile that doesn't inherit from anything as a complete one.
+	fn try_from(profile: PartialProfile) -> Result<Self, Self::Error> {
+		let id = profile.id.clone();
+		let missing = |field| PartialProfileError::MissingField {
+			id: id.clone(),
+			field,
+		};
+		Ok(Profile {
+			arguments: match profile.arguments.ok_or_else(|| missing("arguments"))? {
+				PartialArguments::NewArguments(arguments) => {
+					Arguments::NewArguments(NewArguments {
+						game: arguments.game,
+						jvm: arguments.jvm,
+					})
+				}
+				PartialArguments::OldArguments(arguments) => Arguments::OldArguments(arguments),
+			},
+			asset_index: profile.asset_index.ok_or_else(|| missing("assetIndex"))?,
+			assets: profile.assets.ok_or_else(|| missing("assets"))?,
+			compliance_level: profile.compliance_level.unwrap_or_default(),
+			downloads: profile.downloads.ok_or_else(|| missing("downloads"))?,
+			id: profile.id,
+			java_version: profile.java_version.unwrap_or_default(),
+			libraries: profile.libraries,
+			logging: profile.logging,
+			main_class: profile.main_class.ok_or_else(|| missing("mainClass"))?,
+			minimum_launcher_version: profile
+				.minimum_launcher_version
+				.ok_or_else(|| missing("minimumLauncherVersion"))?,
+			release_time: profile.release_time.ok_or_else(|| missing("releaseTime"))?,
+			time: profile.time.ok_or_else(|| missing("time"))?,
+			version_type: profile.version_type.ok_or_else(|| missing("type"))?,
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	//! These run against version JSONs as Mojang and the loaders publish them (see `tests/fixtures/record.sh`), so they
+	//! check how the profiles relate to each other rather than exact contents.
+
+	use super::*;
+	use crate::instance::libraries;
+
+	fn fixture(name: &str) -> String {
+		let path = format!(
+			"{}/tests/fixtures/profiles/{name}.json",
+			env!("CARGO_MANIFEST_DIR")
+		);
+		std::fs::read_to_string(path).unwrap()
+	}
+
+	fn partial(name: &str) -> PartialProfile {
+		serde_json::from_str(&fixture(name)).unwrap()
+	}
+
+	fn vanilla(name: &str) -> Profile {
+		serde_json::from_str(&fixture(name)).unwrap()
+	}
+
+	fn strings(arguments: &[Argument]) -> Vec<&str> {
+		arguments
+			.iter()
+			.filter_map(|argument| match argument {
+				Argument::String(argument) => Some(argument.as_str()),
+				Argument::Rule(_) => None,
+			})
+			.collect()
+	}
+
+	fn new_arguments(profile: &Profile) -> &NewArguments {
+		match &profile.arguments {
+			Arguments::NewArguments(arguments) => arguments,
+			Arguments::OldArguments(_) => panic!("{} has old-style arguments", profile.id),
+		}
+	}
+
+	/// The child's own arguments, as strings.
+	fn child_arguments(child: &PartialProfile) -> (Vec<&str>, Vec<&str>) {
+		match &child.arguments {
+			Some(PartialArguments::NewArguments(arguments)) => {
+				(strings(&arguments.game), strings(&arguments.jvm))
+			}
+			_ => panic!("{} doesn't have new-style arguments", child.id),
+		}
+	}
+
+	/// Checks that hold for any loader profile merged onto its game version.
+	fn assert_merged(child: &PartialProfile, parent: &Profile, profile: &Profile) {
+		assert_eq!(profile.id, child.id);
+		assert_eq!(profile.assets, parent.assets);
+		assert_eq!(profile.downloads.client.sha1, parent.downloads.client.sha1);
+		assert_eq!(
+			profile.java_version.major_version,
+			parent.java_version.major_version
+		);
+
+		// The child's libraries come first, then the parent's one level up.
+		let children = child.libraries.len();
+		assert_eq!(profile.libraries.len(), children + parent.libraries.len());
+		assert!(profile.libraries[..children]
+			.iter()
+			.zip(&child.libraries)
+			.all(|(merged, library)| merged.name == library.name && merged.inheritance_depth == 0));
+		assert!(profile.libraries[children..]
+			.iter()
+			.zip(&parent.libraries)
+			.all(|(merged, library)| merged.name == library.name && merged.inheritance_depth == 1));
+
+		// Arguments are appended to the parent's.
+		let (game, jvm) = child_arguments(child);
+		let arguments = new_arguments(profile);
+		let parent_arguments = new_arguments(parent);
+		assert_eq!(
+			strings(&arguments.game),
+			[strings(&parent_arguments.game), game].concat()
+		);
+		assert_eq!(
+			strings(&arguments.jvm),
+			[strings(&parent_arguments.jvm), jvm].concat()
+		);
+	}
+
+	#[test]
+	fn vanilla_profiles() {
+		let profile = vanilla("1.20.1");
+		assert_eq!(profile.id, "1.20.1");
+		assert_eq!(profile.main_class, "net.minecraft.client.main.Main");
+		assert_eq!(profile.java_version.major_version, 17);
+		let jvm = strings(&new_arguments(&profile).jvm);
+		assert_eq!(jvm[jvm.len() - 2..], ["-cp", "${classpath}"]);
+
+		let old = vanilla("1.12.2");
+		assert!(!old.supports_quick_play());
+		match &old.arguments {
+			Arguments::OldArguments(arguments) => {
+				assert!(arguments.starts_with("--username ${auth_player_name}"))
+			}
+			Arguments::NewArguments(_) => panic!("1.12.2 has new-style arguments"),
+		}
+	}
+
+	#[test]
+	fn fabric_onto_vanilla() {
+		let parent = vanilla("1.20.1");
+		let child = partial("fabric-loader-0.14.21-1.20.1");
+		let profile = child.clone().merge(parent.clone());
+
+		assert_merged(&child, &parent, &profile);
+		assert_eq!(
+			profile.main_class,
+			"net.fabricmc.loader.impl.launch.knot.KnotClient"
+		);
+		assert_eq!(Some(&profile.release_time), child.release_time.as_ref());
+		assert!(profile.logging.is_some());
+		assert_eq!(
+			strings(&new_arguments(&profile).jvm).last(),
+			Some(&"-DFabricMcEmu= net.minecraft.client.main.Main ")
+		);
+	}
+
+	#[test]
+	fn quilt_onto_vanilla() {
+		let parent = vanilla("1.20.1");
+		let child = partial("quilt-loader-0.19.2-1.20.1");
+		let profile = child.clone().merge(parent.clone());
+
+		assert_merged(&child, &parent, &profile);
+		assert_eq!(
+			profile.main_class,
+			"org.quiltmc.loader.impl.launch.knot.KnotClient"
+		);
+		assert!(profile
+			.libraries
+			.iter()
+			.any(|library| library.name == "org.quiltmc:quilt-loader:0.19.2"));
+	}
+
+	#[test]
+	fn forge_onto_vanilla() {
+		let parent = vanilla("1.20.1");
+		let child = partial("1.20.1-forge-47.1.0");
+		// Forge's `"logging": {}` means the parent's config is used.
+		assert!(child.logging.is_none());
+		let profile = child.clone().merge(parent.clone());
+
+		assert_merged(&child, &parent, &profile);
+		assert_eq!(
+			profile.main_class,
+			"cpw.mods.bootstraplauncher.BootstrapLauncher"
+		);
+		assert_eq!(
+			profile.logging.as_ref().unwrap().client.file.id,
+			parent.logging.unwrap().client.file.id
+		);
+		let game = strings(&new_arguments(&profile).game);
+		assert!(game.contains(&"--launchTarget"));
+		assert!(game
+			.windows(2)
+			.any(|pair| pair == ["--fml.forgeVersion", "47.1.0"]));
+	}
+
+	#[test]
+	fn libraries_override_by_group_and_artifact() {
+		let child = partial("1.20.1-forge-47.1.0");
+		let parent = vanilla("1.20.1");
+		let profile = child.clone().merge(parent.clone());
+		let (resolved, overrides) = libraries::resolve(&profile.libraries);
+
+		// Each library is only on the classpath once, and the child's version wins wherever both have one.
+		let key = |library: &Library| {
+			let coordinate = library.coordinate().unwrap();
+			(coordinate.group, coordinate.artifact, coordinate.classifier)
+		};
+		let mut keys: Vec<_> = resolved.iter().map(key).collect();
+		let count = keys.len();
+		keys.sort();
+		keys.dedup();
+		assert_eq!(keys.len(), count);
+		for library in &child.libraries {
+			let kept = resolved
+				.iter()
+				.find(|resolved| key(resolved) == key(library))
+				.unwrap();
+			assert_eq!(kept.name, library.name);
+			assert_eq!(kept.inheritance_depth, 0);
+		}
+		for library_override in overrides.iter().filter(|found| found.by_inheritance) {
+			assert!(child
+				.libraries
+				.iter()
+				.any(|library| library.name == library_override.kept));
+			assert!(parent
+				.libraries
+				.iter()
+				.any(|library| library.name == library_override.dropped));
+		}
+
+		// A child's older version still wins over its parent's.
+		let mut old = partial("fabric-loader-0.14.21-1.20.1");
+		old.libraries[0].name = "com.google.guava:guava:21.0".into();
+		let (resolved, _) = libraries::resolve(&old.merge(parent).libraries);
+		let guava: Vec<&str> = resolved
+			.iter()
+			.map(|library| library.name.as_str())
+			.filter(|name| name.starts_with("com.google.guava:guava:"))
+			.collect();
+		assert_eq!(guava, ["com.google.guava:guava:21.0"]);
+	}
+
+	#[test]
+	fn new_arguments_onto_old_arguments() {
+		let parent = vanilla("1.12.2");
+		let mut child = partial("fabric-loader-0.14.21-1.20.1");
+		child.inherits_from = Some("1.12.2".into());
+		let (game, jvm) = child_arguments(&child);
+		let (game, jvm) = (
+			game.into_iter().map(String::from).collect::<Vec<_>>(),
+			jvm.into_iter().map(String::from).collect::<Vec<_>>(),
+		);
+		let old_arguments = match &parent.arguments {
+			Arguments::OldArguments(arguments) => arguments.clone(),
+			Arguments::NewArguments(_) => panic!("1.12.2 has new-style arguments"),
+		};
+		let profile = child.merge(parent);
+
+		let arguments = new_arguments(&profile);
+		let expected_game: Vec<&str> = old_arguments
+			.split(' ')
+			.chain(game.iter().map(String::as_str))
+			.collect();
+		assert_eq!(strings(&arguments.game), expected_game);
+		let expected_jvm: Vec<&str> = [
+			"-Djava.library.path=${natives_directory}",
+			"-cp",
+			"${classpath}",
+		]
+		.into_iter()
+		.chain(jvm.iter().map(String::as_str))
+		.collect();
+		assert_eq!(strings(&arguments.jvm), expected_jvm);
+	}
+
+	#[test]
+	fn old_arguments_replace_the_parent() {
+		let mut child = partial("fabric-loader-0.14.21-1.20.1");
+		child.arguments = Some(PartialArguments::OldArguments(
+			"--tweakClass optifine".into(),
+		));
+		let profile = child.merge(vanilla("1.12.2"));
+		match profile.arguments {
+			Arguments::OldArguments(arguments) => assert_eq!(arguments, "--tweakClass optifine"),
+			Arguments::NewArguments(_) => panic!("expected old-style arguments"),
+		}
+	}
+
+	#[test]
+	fn complete_profile_without_parent() {
+		let profile: Profile = partial("1.20.1").try_into().unwrap();
+		assert_eq!(profile.id, "1.20.1");
+
+		let error = Profile::try_from(partial("fabric-loader-0.14.21-1.20.1")).unwrap_err();
+		assert!(matches!(
+			error,
+			PartialProfileError::MissingField {
+				field: "assetIndex",
+				..
+			}
+		));
+	}
+}
@@ -53,14 +53,21 @@ pub enum RuleAction {
 	Disallow,
 }
 
-/// `is_demo_user`: If the Minecraft is running in demo mode.
-/// `has_custom_resolution`: If a custom resolution is being passed to Minecraft.
+/// Features a rule depends on. See [Features](super::Features) for what each one means.
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct RuleItemFeatures {
 	#[serde(skip_serializing_if = "Option::is_none")]
 	pub is_demo_user: Option<bool>,
 	#[serde(skip_serializing_if = "Option::is_none")]
 	pub has_custom_resolution: Option<bool>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub has_quick_plays_support: Option<bool>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub is_quick_play_singleplayer: Option<bool>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub is_quick_play_multiplayer: Option<bool>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub is_quick_play_realms: Option<bool>,
 }
 
 /// `arch`: Architecture of CPU. Observed values: `x86`.
@@ -91,6 +98,9 @@ pub struct Download {
 	#[serde(skip_serializing_if = "Option::is_none")]
 	pub path: Option<String>,
 	pub sha1: String,
+	/// Only set by copper, for files that aren't downloaded but made locally (like a mod loader installer's outputs), which can only be found in the store by hash.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub sha512: Option<String>,
 	pub size: i32,
 	#[serde(alias = "totalSize", skip_serializing_if = "Option::is_none")]
 	pub total_size: Option<i32>,
@@ -151,14 +161,24 @@ impl Default for JavaVersion {
 /// A library that needs to be downloaded and added to the classpath to launch Minecraft.
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Library {
+	/// Mod loader libraries often leave this out, and are fetched from the Maven repository in `url` by their name instead.
+	#[serde(default)]
 	pub downloads: LibraryDownloads,
+	/// Maven coordinate of the library, see [MavenCoordinate](super::maven::MavenCoordinate).
 	pub name: String,
+	/// Maven repository the library is in, if it doesn't have `downloads`.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub url: Option<String>,
 	#[serde(skip_serializing_if = "Option::is_none")]
 	pub rules: Option<Vec<RuleItem>>,
+	/// How many profiles up the inheritance chain the library came from, 0 being the profile itself.
+	/// Used to prefer a child profile's version of a library over its parent's.
+	#[serde(skip)]
+	pub inheritance_depth: usize,
 }
 
 /// Downloads for a library's jar and classifiers (native components of libraries)
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
 pub struct LibraryDownloads {
 	#[serde(skip_serializing_if = "Option::is_none")]
 	pub artifact: Option<Download>,
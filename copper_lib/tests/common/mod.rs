@@ -0,0 +1,79 @@
+//! A local server for the meta API tests, serving recorded responses from `tests/fixtures`.
+
+use copper_lib::api::{fabric, quilt};
+use std::{
+	collections::HashMap,
+	env, fs,
+	io::{BufRead, BufReader, Write},
+	net::TcpListener,
+	path::PathBuf,
+	sync::{Mutex, OnceLock},
+	thread,
+};
+
+/// Responses the server gives, by path.
+static ROUTES: Mutex<Option<HashMap<String, String>>> = Mutex::new(None);
+
+pub fn fixture(path: &str) -> String {
+	fs::read_to_string(
+		PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+			.join("tests/fixtures")
+			.join(path),
+	)
+	.unwrap()
+}
+
+/// Serve `body` at `path`, or stop serving it (so it's a 404) if `body` is `None`.
+pub fn route(path: &str, body: Option<String>) {
+	let mut routes = ROUTES.lock().unwrap();
+	let routes = routes.get_or_insert_with(HashMap::new);
+	match body {
+		Some(body) => routes.insert(path.to_string(), body),
+		None => routes.remove(path),
+	};
+}
+
+/// Start the server (once), and point the meta APIs and the store at it and a temporary directory.
+pub fn server() -> &'static str {
+	static URL: OnceLock<String> = OnceLock::new();
+	URL.get_or_init(|| {
+		let cache = env::temp_dir().join(format!("copper-meta-{}", std::process::id()));
+		fs::create_dir_all(cache.join("copper_launcher/store")).unwrap();
+		env::set_var("XDG_CACHE_HOME", &cache);
+
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let url = format!("http://{}", listener.local_addr().unwrap());
+		env::set_var(fabric::META_URL_VARIABLE, format!("{url}/v2"));
+		env::set_var(quilt::META_URL_VARIABLE, format!("{url}/v3"));
+
+		thread::spawn(move || {
+			for stream in listener.incoming() {
+				let mut stream = stream.unwrap();
+				let mut reader = BufReader::new(&stream);
+				let mut request = String::new();
+				reader.read_line(&mut request).unwrap();
+				// Read the rest of the headers, so the connection closes cleanly.
+				let mut header = String::new();
+				while reader.read_line(&mut header).unwrap() > 2 {
+					header.clear();
+				}
+				let path = request.split(' ').nth(1).unwrap_or_default();
+				let body = ROUTES
+					.lock()
+					.unwrap()
+					.as_ref()
+					.and_then(|routes| routes.get(path).cloned());
+				let response = match body {
+					Some(body) => format!(
+						"HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+						body.len()
+					),
+					None => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+						.to_string(),
+				};
+				stream.write_all(response.as_bytes()).unwrap();
+			}
+		});
+		url
+	})
+}
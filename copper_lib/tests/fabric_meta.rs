@@ -0,0 +1,158 @@
+//! Fabric's meta API, against a local server serving recorded responses.
+
+mod common;
+
+use common::{fixture, route, server};
+use copper_lib::{
+	api::fabric,
+	instance::loader::{Loader, LoaderError},
+	structs::PartialProfile,
+};
+use serde_json::Value;
+
+/// The loader versions in the recorded list, newest first, and whether each is marked stable.
+fn recorded_versions() -> Vec<(String, bool)> {
+	let list: Vec<Value> = serde_json::from_str(&fixture("fabric/loader-1.20.1.json")).unwrap();
+	list.iter()
+		.map(|entry| {
+			(
+				entry["loader"]["version"].as_str().unwrap().to_string(),
+				entry["loader"]["stable"].as_bool().unwrap(),
+			)
+		})
+		.collect()
+}
+
+fn setup() -> &'static str {
+	let url = server();
+	route(
+		"/v2/versions/loader/1.20.1",
+		Some(fixture("fabric/loader-1.20.1.json")),
+	);
+	route("/v2/versions/loader/1.20.2", Some("[]".into()));
+	route(
+		"/v2/versions/loader/1.20.1/0.14.21/profile/json",
+		Some(fixture("profiles/fabric-loader-0.14.21-1.20.1.json")),
+	);
+	url
+}
+
+#[tokio::test]
+async fn lists_loader_versions() {
+	setup();
+	let versions = fabric::FabricMeta::default()
+		.loader_versions("1.20.1")
+		.await
+		.unwrap();
+	let parsed: Vec<(String, bool)> = versions
+		.iter()
+		.map(|entry| (entry.loader.version.clone(), entry.loader.is_stable()))
+		.collect();
+	assert_eq!(parsed, recorded_versions());
+	assert!(parsed.iter().any(|(version, _)| version == "0.14.21"));
+	assert!(versions.iter().all(|entry| entry
+		.intermediary
+		.as_ref()
+		.is_some_and(|intermediary| intermediary.version == "1.20.1")));
+}
+
+#[tokio::test]
+async fn resolves_loader_versions() {
+	setup();
+	let (stable, _) = recorded_versions()
+		.into_iter()
+		.find(|(_, stable)| *stable)
+		.unwrap();
+	for (loader, expected) in [
+		("fabric", stable.as_str()),
+		("fabric@stable", stable.as_str()),
+		("fabric@0.14.21", "0.14.21"),
+	] {
+		let loader: Loader = loader.parse().unwrap();
+		assert_eq!(
+			loader.resolve_version("1.20.1").await.unwrap(),
+			expected,
+			"{loader:?}"
+		);
+	}
+}
+
+#[tokio::test]
+async fn rejects_missing_versions() {
+	setup();
+	let loader: Loader = "fabric@0.99.0".parse().unwrap();
+	let error = loader.resolve_version("1.20.1").await.unwrap_err();
+	assert!(matches!(
+		error.downcast_ref(),
+		Some(LoaderError::UnknownVersion { version, .. }) if version == "0.99.0"
+	));
+
+	let loader: Loader = "fabric".parse().unwrap();
+	let error = loader.resolve_version("1.20.2").await.unwrap_err();
+	assert!(matches!(
+		error.downcast_ref(),
+		Some(LoaderError::NoVersions { game_version, .. }) if game_version == "1.20.2"
+	));
+
+	// Game versions the meta API doesn't know about are a 404.
+	assert!(loader.resolve_version("0.0.1").await.is_err());
+}
+
+#[tokio::test]
+async fn fetches_profiles() {
+	setup();
+	let profile: PartialProfile = fabric::FabricMeta::default()
+		.profile("1.20.1", "0.14.21")
+		.await
+		.unwrap();
+	let recorded: Value =
+		serde_json::from_str(&fixture("profiles/fabric-loader-0.14.21-1.20.1.json")).unwrap();
+	assert_eq!(profile.id, "fabric-loader-0.14.21-1.20.1");
+	assert_eq!(profile.inherits_from.as_deref(), Some("1.20.1"));
+	assert_eq!(
+		profile.libraries.len(),
+		recorded["libraries"].as_array().unwrap().len()
+	);
+	let names: Vec<&str> = profile
+		.libraries
+		.iter()
+		.map(|library| library.name.as_str())
+		.collect();
+	assert!(names.contains(&"net.fabricmc:fabric-loader:0.14.21"));
+	assert!(names.contains(&"net.fabricmc:intermediary:1.20.1"));
+}
+
+#[tokio::test]
+async fn loader_versions_are_always_fresh() {
+	let url = setup();
+	let meta = fabric::FabricMeta::new(format!("{url}/v2"));
+	route(
+		"/v2/versions/loader/fresh",
+		Some(fixture("fabric/loader-1.20.1.json")),
+	);
+	assert_eq!(
+		meta.loader_versions("fresh").await.unwrap().len(),
+		recorded_versions().len()
+	);
+
+	route("/v2/versions/loader/fresh", Some("[]".into()));
+	assert!(meta.loader_versions("fresh").await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn loader_versions_fall_back_to_the_store() {
+	let url = setup();
+	let meta = fabric::FabricMeta::new(format!("{url}/v2"));
+	route(
+		"/v2/versions/loader/unreachable",
+		Some(fixture("fabric/loader-1.20.1.json")),
+	);
+	let versions = meta.loader_versions("unreachable").await.unwrap();
+
+	// Once the meta API can't be reached, the copy from last time is used.
+	route("/v2/versions/loader/unreachable", None);
+	assert_eq!(
+		meta.loader_versions("unreachable").await.unwrap().len(),
+		versions.len()
+	);
+}
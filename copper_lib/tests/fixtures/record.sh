@@ -0,0 +1,31 @@
+#!/bin/sh
+# Records the fixtures in this directory from the real APIs, exactly as they're published.
+# Tests only check how these relate to each other, so they keep passing when the fixtures are recorded again.
+# Needs curl, jq and unzip.
+set -eu
+cd "$(dirname "$0")"
+mkdir -p profiles
+
+manifest=$(curl -fsS https://piston-meta.mojang.com/mc/game/version_manifest_v2.json)
+for version in 1.20.1 1.12.2; do
+	url=$(printf '%s' "$manifest" | jq -r --arg id "$version" '.versions[] | select(.id == $id) | .url')
+	curl -fsS -o "profiles/$version.json" "$url"
+done
+
+curl -fsS -o profiles/fabric-loader-0.14.21-1.20.1.json \
+	https://meta.fabricmc.net/v2/versions/loader/1.20.1/0.14.21/profile/json
+curl -fsS -o profiles/quilt-loader-0.19.2-1.20.1.json \
+	https://meta.quiltmc.org/v3/versions/loader/1.20.1/0.19.2/profile/json
+
+# Forge's profile is only published inside its installer.
+installer=$(mktemp)
+curl -fsS -o "$installer" \
+	https://maven.minecraftforge.net/net/minecraftforge/forge/1.20.1-47.1.0/forge-1.20.1-47.1.0-installer.jar
+unzip -p "$installer" version.json >profiles/1.20.1-forge-47.1.0.json
+rm "$installer"
+
+mkdir -p fabric
+curl -fsS -o fabric/loader-1.20.1.json https://meta.fabricmc.net/v2/versions/loader/1.20.1
+
+mkdir -p quilt
+curl -fsS -o quilt/loader-1.20.1.json https://meta.quiltmc.org/v3/versions/loader/1.20.1
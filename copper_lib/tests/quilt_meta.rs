@@ -0,0 +1,83 @@
+//! Quilt's meta API, against a local server serving recorded responses.
+
+mod common;
+
+use common::{fixture, route, server};
+use copper_lib::{api::quilt, instance::loader::Loader, structs::PartialProfile};
+use serde_json::Value;
+
+/// The loader versions in the recorded list, newest first.
+fn recorded_versions() -> Vec<String> {
+	let list: Vec<Value> = serde_json::from_str(&fixture("quilt/loader-1.20.1.json")).unwrap();
+	list.iter()
+		.map(|entry| entry["loader"]["version"].as_str().unwrap().to_string())
+		.collect()
+}
+
+fn setup() {
+	server();
+	route(
+		"/v3/versions/loader/1.20.1",
+		Some(fixture("quilt/loader-1.20.1.json")),
+	);
+	route(
+		"/v3/versions/loader/1.20.1/0.19.2/profile/json",
+		Some(fixture("profiles/quilt-loader-0.19.2-1.20.1.json")),
+	);
+}
+
+#[tokio::test]
+async fn lists_loader_versions() {
+	setup();
+	let versions = quilt::meta().loader_versions("1.20.1").await.unwrap();
+	let parsed: Vec<&str> = versions
+		.iter()
+		.map(|entry| entry.loader.version.as_str())
+		.collect();
+	assert_eq!(parsed, recorded_versions());
+
+	// Quilt doesn't mark stable versions, so pre-releases are told apart by their suffix.
+	for entry in &versions {
+		assert_eq!(entry.loader.stable, None);
+		assert_eq!(
+			entry.loader.is_stable(),
+			!entry.loader.version.contains('-')
+		);
+	}
+}
+
+#[tokio::test]
+async fn resolves_loader_versions() {
+	setup();
+	let recorded = recorded_versions();
+	let stable = recorded
+		.iter()
+		.find(|version| !version.contains('-'))
+		.unwrap();
+	let pre_release = recorded.iter().find(|version| version.contains('-'));
+
+	let loader: Loader = "quilt".parse().unwrap();
+	assert_eq!(&loader.resolve_version("1.20.1").await.unwrap(), stable);
+	let loader: Loader = "quilt@0.19.2".parse().unwrap();
+	assert_eq!(loader.resolve_version("1.20.1").await.unwrap(), "0.19.2");
+	// Pre-releases can still be picked by pinning them.
+	if let Some(pre_release) = pre_release {
+		let loader: Loader = format!("quilt@{pre_release}").parse().unwrap();
+		assert_eq!(
+			&loader.resolve_version("1.20.1").await.unwrap(),
+			pre_release
+		);
+	}
+}
+
+#[tokio::test]
+async fn fetches_profiles() {
+	setup();
+	let profile: PartialProfile = quilt::meta().profile("1.20.1", "0.19.2").await.unwrap();
+	assert_eq!(profile.id, "quilt-loader-0.19.2-1.20.1");
+	assert_eq!(profile.inherits_from.as_deref(), Some("1.20.1"));
+	assert!(profile
+		.libraries
+		.iter()
+		.any(|library| library.name == "org.quiltmc:quilt-loader:0.19.2"));
+}